@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How an album fared as an answer.
+ */
+export type AlbumStat = { key: string, answer: string, 
+/**
+ * The number of finished games.
+ */
+games: number, 
+/**
+ * The share of games that were solved, from 0 to 1.
+ */
+solve_rate: number, 
+/**
+ * The average number of guesses of the solved games, unset if none was.
+ */
+average_guesses: number | null, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AlbumStat } from "./AlbumStat";
+
+export type AlbumStats = { 
+/**
+ * Hardest first.
+ */
+hardest: Array<AlbumStat>, 
+/**
+ * Easiest first.
+ */
+easiest: Array<AlbumStat>, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Grade } from "./Grade";
+import type { GradingError } from "./GradingError";
+
+export type GuessOutcome = { "Graded": Array<Array<Grade>> } | { "Rejected": GradingError };
@@ -1,3 +1,16 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SeedSource } from "./SeedSource";
 
-export type NewGameResult = { id: string, len: Array<number>, };
+export type NewGameResult = { id: string, len: Array<number>, 
+/**
+ * How hard the answer is expected to be, see [`difficulty_score`].
+ */
+difficulty: number, 
+/**
+ * What the answer pool came from. Artist games are always drawn from the user's top artists.
+ */
+source: SeedSource | null, 
+/**
+ * The number of today's puzzle, for share texts like "Bandordle #123".
+ */
+puzzle_number: bigint, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Grade } from "./Grade";
+
+/**
+ * The grades a guess would get, without making it.
+ */
+export type PreviewResult = { grade: Array<Array<Grade>>, 
+/**
+ * The words that were graded, after lowercasing and dropping accents and punctuation.
+ * Apostrophes are kept if the game was created with them.
+ */
+normalized_guess: Array<string>, };
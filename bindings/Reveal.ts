@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The answer of a finished game.
+ */
+export type Reveal = { answer: string, 
+/**
+ * URL of the artwork of the album, track or artist the answer was drawn from.
+ */
+image: string | null, 
+/**
+ * When the answer is an album, the year it was released, if Last.fm knows it.
+ */
+release_year: number | null, 
+/**
+ * Proves that the board was played to this answer, see [`verify_share`]. Practice games
+ * have none.
+ */
+share_checksum: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Where the answer pool of a game comes from. Sources are tried in the configured order until
+ * one yields an answer, so that players with empty libraries still get a game.
+ */
+export type SeedSource = "top-albums" | "recent-tracks" | "charts";
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Grade } from "./Grade";
+
+/**
+ * The grade of one letter of a guess.
+ */
+export type Tile = { word_index: number, char_index: number, grade: Grade, };
@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Grade } from "./Grade";
+import type { Reveal } from "./Reveal";
+import type { Tile } from "./Tile";
+
+/**
+ * A [`GuessResult`] with its grades also listed tile by tile, in the order they are revealed.
+ */
+export type TiledGuessResult = { tiles: Array<Tile>, grade: Array<Array<Grade>>, 
+/**
+ * The words that were graded, after lowercasing and dropping accents and punctuation.
+ * Apostrophes are kept if the game was created with them.
+ */
+normalized_guess: Array<string>, 
+/**
+ * Milliseconds since the game was started.
+ */
+elapsed_ms: number, 
+/**
+ * How many more guesses the game takes. 0 once it is over.
+ */
+guesses_remaining: number, 
+/**
+ * Set once the game is over.
+ */
+reveal: Reveal | null, };
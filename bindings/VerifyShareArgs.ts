@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Grade } from "./Grade";
+
+export type VerifyShareArgs = { answer: string, 
+/**
+ * The grades of every guess, in order.
+ */
+grades: Array<Array<Array<Grade>>>, checksum: string, };
@@ -0,0 +1,45 @@
+//! Signing runs for every request, so compare it against the plain `write!` based hex encoding it
+//! replaced.
+
+use std::{fmt::Write, hint::black_box};
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use md5::{Digest, Md5};
+
+const ARGS: [(&str, &str); 6] = [
+    ("user", "RJ"),
+    ("period", "overall"),
+    ("page", "1"),
+    ("limit", "1000"),
+    ("method", "user.getTopAlbums"),
+    ("api_key", "b25b959554ed76058ac220b7b2e0a026"),
+];
+
+fn formatted(args: &mut [(&str, &str)], shared_secret: &str) -> String {
+    args.sort_unstable();
+    let mut hasher = Md5::new();
+    for (k, v) in args.iter() {
+        hasher.update(k);
+        hasher.update(v);
+    }
+    hasher.update(shared_secret);
+    let mut signature = String::new();
+    for byte in hasher.finalize() {
+        let _ = write!(&mut signature, "{byte:02x}");
+    }
+    signature
+}
+
+fn sign(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sign");
+    group.bench_function("hex table", |b| {
+        b.iter(|| lastfm::sign(&mut black_box(ARGS), black_box("secret")))
+    });
+    group.bench_function("write!", |b| {
+        b.iter(|| formatted(&mut black_box(ARGS), black_box("secret")))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, sign);
+criterion_main!(benches);
@@ -1,15 +1,21 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use reqwest::Url;
 use serde::{Deserialize, Serialize};
+use url::Url;
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename = "error")]
 pub struct Error {
     #[serde(rename = "@code")]
     pub code: String,
-    #[serde(rename = "$value")]
+    /// Empty when Last.fm only sends the code.
+    #[serde(rename = "$text", default)]
     pub message: String,
+    /// Supplementary detail, from a nested `<context>` element or further `<error>`s that some
+    /// write endpoints send along with the first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
 }
 
 impl Display for Error {
@@ -39,18 +45,41 @@ impl Display for Error {
             }
         };
         write!(f, "{}: {code}: {}", self.code, self.message)?;
+        if let Some(context) = &self.context {
+            write!(f, " ({context})")?;
+        }
         Ok(())
     }
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
 #[serde(tag = "@status", content = "$value")]
 #[serde(rename = "lfm")]
 #[serde(rename_all = "kebab-case")]
 pub enum LfmStatus<T> {
     Ok(T),
+    #[serde(deserialize_with = "merge_errors")]
     Failed(Error),
 }
+
+/// Keep the first of possibly several `<error>`s, folding the others into its context.
+fn merge_errors<'de, D>(deserializer: D) -> Result<Error, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let mut errors = Vec::<Error>::deserialize(deserializer)?.into_iter();
+    let mut error = errors
+        .next()
+        .ok_or_else(|| serde::de::Error::custom("failed response without <error>"))?;
+    let context: Vec<_> = error
+        .context
+        .take()
+        .into_iter()
+        .chain(errors.map(|other| format!("{}: {}", other.code, other.message)))
+        .collect();
+    error.context = (!context.is_empty()).then(|| context.join("; "));
+    Ok(error)
+}
 impl<T> LfmStatus<T> {
     pub fn into_result(self) -> Result<T, Error> {
         match self {
@@ -60,7 +89,8 @@ impl<T> LfmStatus<T> {
     }
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "kebab-case")]
 pub struct Session {
     pub name: String,
@@ -78,11 +108,11 @@ pub struct Session {
 ///   </session>
 /// </lfm>
 /// ```
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 #[serde(transparent)]
 pub struct AuthGetSessionResponse(pub LfmStatus<Session>);
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub enum Period {
     #[serde(rename = "overall")]
     Overall,
@@ -111,15 +141,66 @@ impl Display for Period {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[error("unknown period {0}")]
+pub struct ParsePeriodError(pub String);
+
+impl FromStr for Period {
+    type Err = ParsePeriodError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "overall" => Period::Overall,
+            "7day" => Period::SevenDay,
+            "1month" => Period::OneMonth,
+            "3month" => Period::ThreeMonth,
+            "6month" => Period::SixMonth,
+            "12month" => Period::TwelveMonth,
+            _ => return Err(ParsePeriodError(s.to_owned())),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename = "artist")]
 pub struct ShortArtist {
-    name: String,
-    mbid: String,
-    url: Url,
+    pub name: String,
+    /// Left out of the similar artists of `artist.getInfo`.
+    #[serde(default)]
+    pub mbid: String,
+    pub url: Url,
+    /// Only sent for the similar artists of `artist.getInfo`.
+    #[serde(rename = "image", default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<Image>,
+}
+
+/// A tag as listed in the `getInfo` responses of artists, albums and tracks.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "tag")]
+pub struct ShortTag {
+    pub name: String,
+    pub url: Url,
+    /// How often the tag was applied, only sent by some methods.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<i64>,
+}
+
+/// The tags of an artist, album or track.
+///
+/// Last.fm wraps them in `<tags>` or `<toptags>` depending on the method, and sends an empty
+/// wrapper (or none at all) when there are no tags. Embed this as
+/// `#[serde(alias = "toptags", default)] tags: Tags` to accept all of these.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "tags", default)]
+pub struct Tags {
+    #[serde(rename = "tag")]
+    pub tags: Vec<ShortTag>,
 }
 
-#[derive(Serialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 pub enum ImageSize {
     Small,
@@ -148,20 +229,49 @@ impl<'de> Deserialize<'de> for ImageSize {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+impl ImageSize {
+    /// Position in the order from smallest to largest, with unknown sizes below all others.
+    fn order(&self) -> u8 {
+        match self {
+            ImageSize::Unknown(_) => 0,
+            ImageSize::Small => 1,
+            ImageSize::Medium => 2,
+            ImageSize::Large => 3,
+            ImageSize::Extralarge => 4,
+            ImageSize::Mega => 5,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename = "image")]
 pub struct Image {
     #[serde(rename = "@size")]
-    size: ImageSize,
-    #[serde(rename = "$value")]
-    url: String,
+    pub size: ImageSize,
+    #[serde(rename = "$value", default)]
+    pub url: String,
+}
+
+/// The largest of `images` that actually has a URL; Last.fm sends empty ones for missing artwork.
+pub fn largest(images: &[Image]) -> Option<&Image> {
+    images
+        .iter()
+        .filter(|image| !image.url.is_empty())
+        .max_by_key(|image| image.size.order())
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename = "album")]
 pub struct Album {
-    #[serde(rename = "@rank")]
-    pub rank: i64,
+    #[serde(
+        rename = "@rank",
+        alias = "rank",
+        default,
+        deserialize_with = "lenient::option_i64"
+    )]
+    pub rank: Option<i64>,
     pub name: String,
     pub playcount: i64,
     pub mbid: String,
@@ -171,11 +281,17 @@ pub struct Album {
     pub images: Vec<Image>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename = "artist")]
 pub struct Artist {
-    #[serde(rename = "@rank")]
-    pub rank: i64,
+    #[serde(
+        rename = "@rank",
+        alias = "rank",
+        default,
+        deserialize_with = "lenient::option_i64"
+    )]
+    pub rank: Option<i64>,
     pub name: String,
     pub playcount: i64,
     pub mbid: String,
@@ -185,12 +301,21 @@ pub struct Artist {
     pub images: Vec<Image>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename = "artist")]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "track")]
 pub struct Track {
-    #[serde(rename = "@rank")]
-    pub rank: i64,
+    #[serde(
+        rename = "@rank",
+        alias = "rank",
+        default,
+        deserialize_with = "lenient::option_i64"
+    )]
+    pub rank: Option<i64>,
     pub name: String,
+    /// Length in seconds, 0 if unknown.
+    #[serde(default)]
+    pub duration: i64,
     pub playcount: i64,
     pub mbid: String,
     pub url: Url,
@@ -200,289 +325,3192 @@ pub struct Track {
     pub images: Vec<Image>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename = "topalbums")]
 pub struct TopAlbums {
     #[serde(rename = "@user")]
     pub user: String,
-    // #[serde(rename = "@type")]
-    // typ: Period,
-    #[serde(rename = "$value")]
+    #[serde(rename = "@type", default)]
+    pub period: Option<Period>,
+    #[serde(rename = "@page", default)]
+    pub page: usize,
+    #[serde(rename = "@perPage", default)]
+    pub per_page: usize,
+    #[serde(rename = "@totalPages", default)]
+    pub total_pages: usize,
+    /// The number of entries across all pages.
+    #[serde(rename = "@total", default)]
+    pub total: usize,
+    #[serde(rename = "$value", default)]
     pub albums: Vec<Album>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename = "topartists")]
 pub struct TopArtists {
     #[serde(rename = "@user")]
     pub user: String,
-
-    #[serde(rename = "$value")]
+    #[serde(rename = "@type", default)]
+    pub period: Option<Period>,
+    #[serde(rename = "@page", default)]
+    pub page: usize,
+    #[serde(rename = "@perPage", default)]
+    pub per_page: usize,
+    #[serde(rename = "@totalPages", default)]
+    pub total_pages: usize,
+    /// The number of entries across all pages.
+    #[serde(rename = "@total", default)]
+    pub total: usize,
+    #[serde(rename = "$value", default)]
     pub artists: Vec<Artist>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+/// An album among the top albums of a tag. Unlike an [`Album`], it has no play count.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "album")]
+pub struct TagAlbum {
+    #[serde(
+        rename = "@rank",
+        alias = "rank",
+        default,
+        deserialize_with = "lenient::option_i64"
+    )]
+    pub rank: Option<i64>,
+    pub name: String,
+    #[serde(default)]
+    pub mbid: String,
+    pub url: Url,
+    pub artist: ShortArtist,
+    #[serde(rename = "image", default)]
+    pub images: Vec<Image>,
+}
+
+/// The albums most tagged with a tag, from `tag.getTopAlbums`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "albums")]
+pub struct TagTopAlbums {
+    #[serde(rename = "@tag")]
+    pub tag: String,
+    #[serde(rename = "@page", default)]
+    pub page: usize,
+    #[serde(rename = "@perPage", default)]
+    pub per_page: usize,
+    #[serde(rename = "@totalPages", default)]
+    pub total_pages: usize,
+    /// The number of entries across all pages.
+    #[serde(rename = "@total", default)]
+    pub total: usize,
+    #[serde(rename = "album", default)]
+    pub albums: Vec<TagAlbum>,
+}
+
+/// An artist among the top artists of a tag. Unlike an [`Artist`], it has no play count.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "artist")]
+pub struct TagArtist {
+    #[serde(
+        rename = "@rank",
+        alias = "rank",
+        default,
+        deserialize_with = "lenient::option_i64"
+    )]
+    pub rank: Option<i64>,
+    pub name: String,
+    #[serde(default)]
+    pub mbid: String,
+    pub url: Url,
+    #[serde(default)]
+    pub streamable: bool,
+    #[serde(rename = "image", default)]
+    pub images: Vec<Image>,
+}
+
+/// The artists most tagged with a tag, from `tag.getTopArtists`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "topartists")]
+pub struct TagTopArtists {
+    #[serde(rename = "@tag")]
+    pub tag: String,
+    #[serde(rename = "@page", default)]
+    pub page: usize,
+    #[serde(rename = "@perPage", default)]
+    pub per_page: usize,
+    #[serde(rename = "@totalPages", default)]
+    pub total_pages: usize,
+    /// The number of entries across all pages.
+    #[serde(rename = "@total", default)]
+    pub total: usize,
+    #[serde(rename = "artist", default)]
+    pub artists: Vec<TagArtist>,
+}
+
+/// A tag as described by `tag.getInfo`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "tag")]
+pub struct TagInfo {
+    pub name: String,
+    /// Not sent by `tag.getInfo` for most tags.
+    #[serde(default)]
+    pub url: Option<Url>,
+    /// How often the tag was applied.
+    #[serde(default)]
+    pub total: i64,
+    /// How many users applied the tag.
+    #[serde(default)]
+    pub reach: i64,
+    #[serde(default)]
+    pub wiki: Option<chart::Wiki>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename = "toptracks")]
 pub struct TopTracks {
     #[serde(rename = "@user")]
     pub user: String,
+    #[serde(rename = "@type", default)]
+    pub period: Option<Period>,
+    #[serde(rename = "@page", default)]
+    pub page: usize,
+    #[serde(rename = "@perPage", default)]
+    pub per_page: usize,
+    #[serde(rename = "@totalPages", default)]
+    pub total_pages: usize,
+    /// The number of entries across all pages.
+    #[serde(rename = "@total", default)]
+    pub total: usize,
+    #[serde(rename = "$value", default)]
+    pub tracks: Vec<Track>,
+}
 
-    #[serde(rename = "$value")]
-    pub artists: Vec<Track>,
+/// The artist of a weekly chart entry, `<artist mbid="...">Name</artist>`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "artist")]
+pub struct ChartArtist {
+    #[serde(rename = "@mbid", default)]
+    pub mbid: String,
+    #[serde(rename = "$text")]
+    pub name: String,
 }
 
-pub mod chart {
-    use super::*;
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "album")]
+pub struct WeeklyAlbum {
+    #[serde(rename = "@rank")]
+    pub rank: i64,
+    pub artist: ChartArtist,
+    pub name: String,
+    pub mbid: String,
+    pub playcount: i64,
+    pub url: Url,
+}
 
-    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-    #[serde(rename = "wiki")]
-    pub struct Wiki {
-        published: String,
-        summary: String,
-        content: String,
-    }
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "weeklyalbumchart")]
+pub struct WeeklyAlbumChart {
+    #[serde(rename = "@user")]
+    pub user: String,
+    /// Start of the chart as a unix timestamp.
+    #[serde(rename = "@from")]
+    pub from: i64,
+    /// End of the chart as a unix timestamp.
+    #[serde(rename = "@to")]
+    pub to: i64,
+    #[serde(rename = "album", default)]
+    pub albums: Vec<WeeklyAlbum>,
+}
 
-    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-    #[serde(rename = "artists")]
-    pub struct Artist {
-        name: String,
-        playcount: i64,
-        listeners: i64,
-        mbid: String,
-        url: Url,
-        streamable: bool,
-        #[serde(rename = "$value")]
-        images: Vec<Image>,
-    }
+/// The range of one weekly chart, `<chart from="..." to="..."/>`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "chart")]
+pub struct ChartRange {
+    /// Start of the chart as a unix timestamp.
+    #[serde(rename = "@from")]
+    pub from: i64,
+    /// End of the chart as a unix timestamp.
+    #[serde(rename = "@to")]
+    pub to: i64,
+}
 
-    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-    #[serde(rename = "tag")]
-    pub struct Tag {
-        name: String,
-        url: Url,
-        reach: i64,
-        taggings: i64,
-        streamable: bool,
-        wiki: Wiki,
-    }
+/// The weekly charts Last.fm computed for a user, which the `from` and `to` of the weekly chart
+/// methods have to match.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "weeklychartlist")]
+pub struct WeeklyChartList {
+    #[serde(rename = "@user", default)]
+    pub user: String,
+    /// Oldest first.
+    #[serde(rename = "chart", default)]
+    pub charts: Vec<ChartRange>,
+}
 
-    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-    #[serde(rename = "track")]
-    pub struct Track {
-        name: String,
-        playcount: i64,
-        listeners: i64,
-        mbid: Option<String>,
-        url: Url,
-        streamable: bool,
-        artist: ShortArtist,
-    }
+/// The album of a recent track, `<album mbid="...">Name</album>`. Empty if Last.fm does not know
+/// it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "album")]
+pub struct RecentAlbum {
+    #[serde(rename = "@mbid", default)]
+    pub mbid: String,
+    #[serde(rename = "$text", default)]
+    pub name: String,
+}
 
-    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-    #[serde(rename = "artists")]
-    pub struct TopArtists {
-        #[serde(rename = "@page")]
-        page: usize,
-        #[serde(rename = "@perPage")]
-        per_page: usize,
-        #[serde(rename = "@totalPages")]
-        total_pages: usize,
-        #[serde(rename = "@total")]
-        total: usize,
-        #[serde(rename = "$value")]
-        artists: Vec<Artist>,
-    }
+/// The artist of a recent track.
+///
+/// Plain requests send `<artist mbid="...">Name</artist>`, extended ones nest the name, mbid, URL
+/// and images as elements instead.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename = "artist", from = "RawRecentArtist")]
+pub struct RecentArtist {
+    pub name: String,
+    pub mbid: String,
+    /// Only sent by extended requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<Url>,
+    /// Only sent by extended requests.
+    #[serde(rename = "image", default)]
+    pub images: Vec<Image>,
+}
 
-    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-    #[serde(rename = "tags")]
-    pub struct TopTags {
-        #[serde(rename = "@page")]
-        page: usize,
-        #[serde(rename = "@perPage")]
-        per_page: usize,
-        #[serde(rename = "@totalPages")]
-        total_pages: usize,
-        #[serde(rename = "@total")]
-        total: usize,
+/// Both forms of [`RecentArtist`].
+#[derive(Deserialize)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+struct RawRecentArtist {
+    #[serde(rename = "@mbid", default)]
+    mbid_attribute: String,
+    #[serde(rename = "$text", default)]
+    text: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    mbid: Option<String>,
+    #[serde(default)]
+    url: Option<Url>,
+    #[serde(rename = "image", default)]
+    images: Vec<Image>,
+}
 
-        tags: Vec<Tag>,
+impl From<RawRecentArtist> for RecentArtist {
+    fn from(raw: RawRecentArtist) -> Self {
+        RecentArtist {
+            name: raw.name.unwrap_or(raw.text),
+            mbid: raw.mbid.unwrap_or(raw.mbid_attribute),
+            url: raw.url,
+            images: raw.images,
+        }
     }
+}
 
-    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-    #[serde(rename = "tags")]
-    pub struct TopTracks {
-        #[serde(rename = "@page")]
-        page: usize,
-        #[serde(rename = "@perPage")]
-        per_page: usize,
-        #[serde(rename = "@totalPages")]
-        total_pages: usize,
-        #[serde(rename = "@total")]
-        total: usize,
+/// When a track was scrobbled.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "date")]
+pub struct ScrobbleDate {
+    /// Unix timestamp.
+    #[serde(rename = "@uts")]
+    pub uts: i64,
+    /// Human readable form, e.g. `9 Jun 2008, 17:16`.
+    #[serde(rename = "$text")]
+    pub text: String,
+}
 
-        tags: Vec<Track>,
-    }
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "track")]
+pub struct RecentTrack {
+    /// Whether the user is listening to this track right now. Such a track has no `date`.
+    #[serde(rename = "@nowplaying", default)]
+    pub now_playing: bool,
+    pub artist: RecentArtist,
+    /// Whether the user loved the track. Only sent by extended requests.
+    #[serde(default)]
+    pub loved: bool,
+    pub name: String,
+    #[serde(default)]
+    pub streamable: bool,
+    pub mbid: String,
+    pub album: RecentAlbum,
+    pub url: Url,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<ScrobbleDate>,
+    #[serde(rename = "$value", default)]
+    pub images: Vec<Image>,
+}
 
-    pub type GetTopArtistsResponse = LfmStatus<TopArtists>;
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "recenttracks")]
+pub struct RecentTracks {
+    #[serde(rename = "@user")]
+    pub user: String,
+    #[serde(rename = "@page", default)]
+    pub page: usize,
+    #[serde(rename = "@perPage", default)]
+    pub per_page: usize,
+    #[serde(rename = "@totalPages", default)]
+    pub total_pages: usize,
+    /// The number of scrobbles across all pages.
+    #[serde(rename = "@total", default)]
+    pub total: usize,
+    /// Most recent first, starting with the currently playing track if there is one.
+    #[serde(rename = "track", default)]
+    pub tracks: Vec<RecentTrack>,
+}
 
-    pub type GetTopTagsResponse = LfmStatus<TopTags>;
+/// A track a user loved.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "track")]
+pub struct LovedTrack {
+    pub name: String,
+    #[serde(default)]
+    pub mbid: String,
+    pub url: Url,
+    /// When the track was loved.
+    pub date: ScrobbleDate,
+    pub artist: ShortArtist,
+    #[serde(rename = "image", default)]
+    pub images: Vec<Image>,
+    #[serde(default)]
+    pub streamable: bool,
+}
 
-    pub type GetTopTracksResponse = LfmStatus<TopTracks>;
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "lovedtracks")]
+pub struct LovedTracks {
+    #[serde(rename = "@user")]
+    pub user: String,
+    #[serde(rename = "@page", default)]
+    pub page: usize,
+    #[serde(rename = "@perPage", default)]
+    pub per_page: usize,
+    #[serde(rename = "@totalPages", default)]
+    pub total_pages: usize,
+    /// The number of loved tracks across all pages.
+    #[serde(rename = "@total", default)]
+    pub total: usize,
+    /// Most recently loved first.
+    #[serde(rename = "track", default)]
+    pub tracks: Vec<LovedTrack>,
 }
 
-pub type GetTopAlbumsResponse = LfmStatus<TopAlbums>;
+/// An album a user tagged.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "album")]
+pub struct TaggedAlbum {
+    pub name: String,
+    #[serde(default)]
+    pub mbid: String,
+    pub url: Url,
+    pub artist: ShortArtist,
+    #[serde(rename = "image", default)]
+    pub images: Vec<Image>,
+}
 
-pub type GetTopArtistsResponse = LfmStatus<TopArtists>;
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "albums", default)]
+pub struct TaggedAlbums {
+    #[serde(rename = "album")]
+    pub albums: Vec<TaggedAlbum>,
+}
 
-pub type GetTopTracksResponse = LfmStatus<TopTracks>;
+/// The albums a user tagged with a tag, from `user.getPersonalTags` with `taggingtype=album`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "taggings")]
+pub struct PersonalTagAlbums {
+    #[serde(rename = "@user")]
+    pub user: String,
+    #[serde(rename = "@tag")]
+    pub tag: String,
+    #[serde(rename = "@page", default)]
+    pub page: usize,
+    #[serde(rename = "@perPage", default)]
+    pub per_page: usize,
+    #[serde(rename = "@totalPages", default)]
+    pub total_pages: usize,
+    /// The number of tagged albums across all pages.
+    #[serde(rename = "@total", default)]
+    pub total: usize,
+    /// Left out when the user tagged no albums with the tag.
+    #[serde(default)]
+    pub albums: TaggedAlbums,
+}
 
-#[cfg(test)]
-mod tests {
+/// When a user signed up.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "registered")]
+pub struct Registered {
+    /// Unix timestamp.
+    #[serde(rename = "@unixtime")]
+    pub unixtime: i64,
+    /// Human readable form, e.g. `2007-08-15 13:53`.
+    #[serde(rename = "$text", default)]
+    pub text: String,
+}
 
-    use super::*;
+/// A Last.fm user, as listed among someone's friends or described by `user.getInfo`.
+///
+/// The fields that are `Option`s are only sent by `user.getInfo`, which profiles a single user.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "user")]
+pub struct UserSummary {
+    pub name: String,
+    /// Empty unless the user filled it in.
+    #[serde(default)]
+    pub realname: String,
+    pub url: Url,
+    #[serde(default)]
+    pub country: String,
+    #[serde(default)]
+    pub subscriber: bool,
+    /// The number of scrobbles.
+    #[serde(default)]
+    pub playcount: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registered: Option<Registered>,
+    #[serde(rename = "image", default)]
+    pub images: Vec<Image>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gender: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub playlists: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bootstrap: Option<u64>,
+    /// The kind of account, e.g. `user`, `subscriber` or `staff`.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub account_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artist_count: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub album_count: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_count: Option<u64>,
+}
 
-    use pretty_assertions::assert_eq;
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "friends")]
+pub struct Friends {
+    /// The user whose friends these are.
+    #[serde(rename = "@for")]
+    pub user: String,
+    #[serde(rename = "@page", default)]
+    pub page: usize,
+    #[serde(rename = "@perPage", default)]
+    pub per_page: usize,
+    #[serde(rename = "@totalPages", default)]
+    pub total_pages: usize,
+    /// The number of friends across all pages.
+    #[serde(rename = "@total", default)]
+    pub total: usize,
+    #[serde(rename = "user", default)]
+    pub friends: Vec<UserSummary>,
+}
 
-    use quick_xml::de::from_str;
+/// A track similar to the one asked for in `track.getSimilar`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "track")]
+pub struct SimilarTrack {
+    pub name: String,
+    #[serde(default)]
+    pub playcount: i64,
+    /// Missing for tracks MusicBrainz does not know.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mbid: Option<String>,
+    /// How similar the track is, from 0 to 1.
+    #[serde(rename = "match")]
+    pub match_score: f64,
+    pub url: Url,
+    #[serde(default)]
+    pub streamable: bool,
+    /// Length in seconds, 0 if unknown.
+    #[serde(default)]
+    pub duration: i64,
+    pub artist: ShortArtist,
+    #[serde(rename = "$value", default)]
+    pub images: Vec<Image>,
+}
 
-    fn i(size: ImageSize, url: &str) -> Image {
-        Image {
-            size,
-            url: url.into(),
-        }
-    }
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "similartracks")]
+pub struct SimilarTracks {
+    /// The track the others are similar to, after autocorrection.
+    #[serde(rename = "@track", default)]
+    pub track: String,
+    #[serde(rename = "@artist", default)]
+    pub artist: String,
+    /// Most similar first.
+    #[serde(rename = "track", default)]
+    pub tracks: Vec<SimilarTrack>,
+}
 
-    #[test]
-    fn status_failed() {
-        let x: LfmStatus<()> = from_str(
-            r#"
-            <lfm status="failed">
-                <error code="10">Invalid API Key</error>
-            </lfm>
-            "#,
-        )
-        .expect("can parse xml");
-        assert_eq!(
-            x,
-            LfmStatus::Failed(Error {
-                code: "10".into(),
-                message: "Invalid API Key".into()
-            })
-        );
-    }
+/// An artist similar to the one asked for in `artist.getSimilar`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "artist")]
+pub struct SimilarArtist {
+    pub name: String,
+    /// Missing for artists MusicBrainz does not know.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mbid: Option<String>,
+    /// How similar the artist is, from 0 to 1.
+    #[serde(rename = "match")]
+    pub match_score: f64,
+    pub url: Url,
+    #[serde(rename = "image", default)]
+    pub images: Vec<Image>,
+    #[serde(default)]
+    pub streamable: bool,
+}
 
-    #[test]
-    fn auth_get_session_response() {
-        let x: AuthGetSessionResponse = from_str(
-            r#"<lfm status="ok">
-                <session>
-                <name>MyLastFMUsername</name>
-                <key>d580d57f32848f5dcf574d1ce18d78b2</key>
-                <subscriber>0</subscriber>
-                </session>
-            </lfm>"#,
-        )
-        .expect("can parse");
-        assert_eq!(
-            x,
-            AuthGetSessionResponse(LfmStatus::Ok(Session {
-                name: "MyLastFMUsername".into(),
-                key: "d580d57f32848f5dcf574d1ce18d78b2".into(),
-                subscriber: 0
-            }))
-        );
-    }
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "similarartists")]
+pub struct SimilarArtists {
+    /// The artist the others are similar to, after autocorrection.
+    #[serde(rename = "@artist", default)]
+    pub artist: String,
+    /// Most similar first.
+    #[serde(rename = "artist", default)]
+    pub artists: Vec<SimilarArtist>,
+}
 
-    #[test]
-    fn user_get_top_albums_response() {
-        let x: TopAlbums = from_str(
-            r#"<topalbums user="RJ" type="overall">
-<album rank="1">
-  <name>Images and Words</name>
-  <playcount>174</playcount>
-  <mbid>f20971f2-c8ad-4d26-91ab-730f6dedafb2</mbid>  
-  <url>
-    http://www.last.fm/music/Dream+Theater/Images+and+Words
-  </url>
-  <artist>
-    <name>Dream Theater</name>
-    <mbid>28503ab7-8bf2-4666-a7bd-2644bfc7cb1d</mbid>
-    <url>http://www.last.fm/music/Dream+Theater</url>
-  </artist>
-  <image size="small">...</image>
-  <image size="medium">...</image>
-  <image size="large">...</image>
-</album>
-</topalbums>"#,
-        )
-        .expect("can parse");
-        assert_eq!(
+/// The `<opensearch:Query>` a search response echoes.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "Query", default)]
+pub struct SearchQuery {
+    #[serde(rename = "@role")]
+    pub role: String,
+    #[serde(rename = "@searchTerms")]
+    pub search_terms: String,
+    #[serde(
+        rename = "@startPage",
+        deserialize_with = "lenient::option_i64",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub start_page: Option<i64>,
+}
+
+/// A track found by `track.search`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "track")]
+pub struct TrackMatch {
+    pub name: String,
+    /// Only the name, like in [`AlbumInfo`].
+    pub artist: String,
+    pub url: Url,
+    #[serde(default)]
+    pub streamable: bool,
+    #[serde(default, deserialize_with = "lenient::option_u64")]
+    pub listeners: Option<u64>,
+    #[serde(default)]
+    pub mbid: String,
+    #[serde(rename = "image", default)]
+    pub images: Vec<Image>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "trackmatches", default)]
+pub struct TrackMatches {
+    #[serde(rename = "track")]
+    pub tracks: Vec<TrackMatch>,
+}
+
+/// An artist found by `artist.search`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "artist")]
+pub struct ArtistMatch {
+    pub name: String,
+    #[serde(default, deserialize_with = "lenient::option_u64")]
+    pub listeners: Option<u64>,
+    #[serde(default)]
+    pub mbid: String,
+    pub url: Url,
+    #[serde(default)]
+    pub streamable: bool,
+    #[serde(rename = "image", default)]
+    pub images: Vec<Image>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "artistmatches", default)]
+pub struct ArtistMatches {
+    #[serde(rename = "artist")]
+    pub artists: Vec<ArtistMatch>,
+}
+
+/// An album found by `album.search`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "album")]
+pub struct AlbumMatch {
+    pub name: String,
+    /// Only the name, like in [`AlbumInfo`].
+    pub artist: String,
+    pub url: Url,
+    #[serde(default)]
+    pub streamable: bool,
+    /// Missing for most albums.
+    #[serde(
+        default,
+        deserialize_with = "lenient::non_empty",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mbid: Option<String>,
+    #[serde(rename = "image", default)]
+    pub images: Vec<Image>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "albummatches", default)]
+pub struct AlbumMatches {
+    #[serde(rename = "album")]
+    pub albums: Vec<AlbumMatch>,
+}
+
+/// One page of the results of a search, best matches first.
+///
+/// quick-xml matches elements by their local name, so `<opensearch:totalResults>` is
+/// `totalResults` here. Only attributes keep their prefix.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "results")]
+pub struct SearchResults<M> {
+    /// What was searched for.
+    #[serde(rename = "@for", default)]
+    pub search: String,
+    #[serde(
+        rename = "@xmlns:opensearch",
+        default,
+        skip_serializing_if = "String::is_empty"
+    )]
+    opensearch_namespace: String,
+    #[serde(rename = "Query", default)]
+    pub query: SearchQuery,
+    /// The number of matches across all pages.
+    #[serde(rename = "totalResults", default)]
+    pub total_results: u64,
+    /// The offset of the first match of this page, counting from 0.
+    #[serde(rename = "startIndex", default)]
+    pub start_index: u64,
+    #[serde(rename = "itemsPerPage", default)]
+    pub items_per_page: u64,
+    /// Wrapped in `<trackmatches>`, `<artistmatches>` and so on depending on the method.
+    #[serde(
+        alias = "trackmatches",
+        alias = "artistmatches",
+        alias = "albummatches",
+        default
+    )]
+    pub matches: M,
+}
+
+impl<M> SearchResults<M> {
+    /// Whether there are matches after this page.
+    pub fn has_more(&self) -> bool {
+        self.start_index + self.items_per_page < self.total_results
+    }
+}
+
+/// The results of `track.search`.
+pub type TrackSearchResults = SearchResults<TrackMatches>;
+
+/// The results of `artist.search`.
+pub type ArtistSearchResults = SearchResults<ArtistMatches>;
+
+/// The results of `album.search`.
+pub type AlbumSearchResults = SearchResults<AlbumMatches>;
+
+/// How popular an artist is, `<stats>` of `artist.getInfo`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "stats", default)]
+pub struct ArtistStats {
+    pub listeners: u64,
+    pub playcount: u64,
+    /// How often the `username` given in the request listened to the artist.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub userplaycount: Option<u64>,
+}
+
+/// The name Last.fm corrects an artist name to, from `artist.getCorrection`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "correction")]
+pub struct ArtistCorrectionEntry {
+    #[serde(rename = "@index", default)]
+    pub index: usize,
+    pub artist: ShortArtist,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "corrections", default)]
+pub struct ArtistCorrection {
+    /// Missing when Last.fm knows no correction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correction: Option<ArtistCorrectionEntry>,
+}
+
+/// A few similar artists, `<similar>` of `artist.getInfo`. `artist.getSimilar` lists more, as
+/// [`SimilarArtists`] with how similar they are.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "similar", default)]
+pub struct ArtistInfoSimilar {
+    /// Most similar first.
+    #[serde(rename = "artist")]
+    pub artists: Vec<ShortArtist>,
+}
+
+/// A link from an artist's biography, e.g. `<link rel="original" href="..."/>` to the full text.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "link")]
+pub struct BioLink {
+    #[serde(rename = "@rel", default)]
+    pub rel: String,
+    #[serde(rename = "@href")]
+    pub href: Url,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "links", default)]
+pub struct BioLinks {
+    #[serde(rename = "link")]
+    pub links: Vec<BioLink>,
+}
+
+/// The biography of an artist. Like a [`chart::Wiki`], but with links.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "bio", default)]
+pub struct Bio {
+    pub links: BioLinks,
+    pub published: String,
+    pub summary: String,
+    pub content: String,
+}
+
+/// An artist as described by `artist.getInfo`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "artist")]
+pub struct ArtistInfo {
+    pub name: String,
+    #[serde(default)]
+    pub mbid: String,
+    pub url: Url,
+    #[serde(rename = "image", default)]
+    pub images: Vec<Image>,
+    #[serde(default)]
+    pub streamable: bool,
+    #[serde(default)]
+    pub ontour: bool,
+    #[serde(default)]
+    pub stats: ArtistStats,
+    #[serde(default)]
+    pub similar: ArtistInfoSimilar,
+    #[serde(alias = "toptags", default)]
+    pub tags: Tags,
+    #[serde(default)]
+    pub bio: Bio,
+}
+
+/// The album a track is on, as described by `track.getInfo`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "album")]
+pub struct TrackAlbum {
+    /// The position of the track on the album.
+    #[serde(
+        rename = "@position",
+        default,
+        deserialize_with = "lenient::option_i64"
+    )]
+    pub position: Option<i64>,
+    #[serde(rename = "title")]
+    pub name: String,
+    /// Only the name, like in [`AlbumInfo`].
+    pub artist: String,
+    #[serde(default)]
+    pub mbid: String,
+    pub url: Url,
+    #[serde(rename = "image", default)]
+    pub images: Vec<Image>,
+}
+
+/// A track as described by `track.getInfo`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "track")]
+pub struct TrackInfo {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mbid: Option<String>,
+    pub url: Url,
+    /// Length in milliseconds, unlike the seconds of other responses.
+    #[serde(default, deserialize_with = "lenient::option_u64")]
+    pub duration: Option<u64>,
+    #[serde(default)]
+    pub streamable: bool,
+    #[serde(default)]
+    pub listeners: u64,
+    #[serde(default)]
+    pub playcount: u64,
+    pub artist: ShortArtist,
+    /// Missing for tracks Last.fm knows no album of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub album: Option<TrackAlbum>,
+    /// How often the `username` given in the request listened to the track.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub userplaycount: Option<u64>,
+    /// Whether the `username` given in the request loved the track.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub userloved: Option<bool>,
+    #[serde(alias = "toptags", default)]
+    pub tags: Tags,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wiki: Option<chart::Wiki>,
+}
+
+/// A track on the track list of an album.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "track")]
+pub struct AlbumTrack {
+    /// The position on the album.
+    #[serde(
+        rename = "@rank",
+        alias = "rank",
+        default,
+        deserialize_with = "lenient::option_i64"
+    )]
+    pub rank: Option<i64>,
+    pub name: String,
+    pub url: Url,
+    /// Length in seconds.
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub duration: Option<i64>,
+    #[serde(default)]
+    pub streamable: bool,
+    pub artist: ShortArtist,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "tracks", default)]
+pub struct AlbumTracks {
+    #[serde(rename = "track")]
+    pub tracks: Vec<AlbumTrack>,
+}
+
+/// An album as described by `album.getInfo`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename = "album")]
+pub struct AlbumInfo {
+    pub name: String,
+    /// Only the name, unlike the [`ShortArtist`] of other responses.
+    pub artist: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mbid: Option<String>,
+    pub url: Url,
+    /// Sent for few albums nowadays, e.g. `    6 Apr 1999, 00:00`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub releasedate: Option<String>,
+    #[serde(rename = "image", default)]
+    pub images: Vec<Image>,
+    #[serde(default)]
+    pub listeners: i64,
+    #[serde(default)]
+    pub playcount: i64,
+    /// How often the `username` given in the request listened to the album.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub userplaycount: Option<i64>,
+    #[serde(default)]
+    pub tracks: AlbumTracks,
+    #[serde(alias = "toptags", default)]
+    pub tags: Tags,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wiki: Option<chart::Wiki>,
+}
+
+impl AlbumInfo {
+    /// The year the album came out, if Last.fm knows it.
+    ///
+    /// Read from the release date, or else from the publishing date of the wiki article, which
+    /// is mostly written around the release. Both are free-form text like `27 Jul 2008, 15:55`.
+    pub fn release_year(&self) -> Option<i32> {
+        fn year(date: &str) -> Option<i32> {
+            date.split(|c: char| !c.is_ascii_digit())
+                .find(|part| part.len() == 4)
+                .and_then(|year| year.parse().ok())
+        }
+
+        self.releasedate
+            .as_deref()
+            .and_then(year)
+            .or_else(|| self.wiki.as_ref().and_then(|wiki| year(&wiki.published)))
+    }
+}
+
+pub mod chart {
+    use super::*;
+
+    /// A wiki article. Last.fm sends an empty `<wiki>` when there is none.
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+    #[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+    #[serde(rename = "wiki", default)]
+    pub struct Wiki {
+        pub published: String,
+        pub summary: String,
+        pub content: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+    #[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+    #[serde(rename = "artist")]
+    pub struct Artist {
+        pub name: String,
+        pub playcount: i64,
+        pub listeners: i64,
+        pub mbid: String,
+        pub url: Url,
+        pub streamable: bool,
+        #[serde(rename = "$value")]
+        pub images: Vec<Image>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+    #[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+    #[serde(rename = "tag")]
+    pub struct Tag {
+        pub name: String,
+        pub url: Url,
+        pub reach: i64,
+        pub taggings: i64,
+        pub streamable: bool,
+        pub wiki: Wiki,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+    #[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+    #[serde(rename = "track")]
+    pub struct Track {
+        pub name: String,
+        /// Length in seconds, 0 if unknown.
+        #[serde(default)]
+        pub duration: i64,
+        pub playcount: i64,
+        pub listeners: i64,
+        pub mbid: String,
+        pub url: Url,
+        pub streamable: bool,
+        pub artist: ShortArtist,
+        #[serde(rename = "$value", default)]
+        pub images: Vec<Image>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+    #[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+    #[serde(rename = "artists")]
+    pub struct TopArtists {
+        #[serde(rename = "@page")]
+        pub page: usize,
+        #[serde(rename = "@perPage")]
+        pub per_page: usize,
+        #[serde(rename = "@totalPages")]
+        pub total_pages: usize,
+        #[serde(rename = "@total")]
+        pub total: usize,
+        #[serde(rename = "$value")]
+        pub artists: Vec<Artist>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+    #[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+    #[serde(rename = "tags")]
+    pub struct TopTags {
+        // the XML API puts the paging on `<tags>` like on the other charts; only the JSON API
+        // nests it in an `@attr` object
+        #[serde(rename = "@page", default)]
+        pub page: usize,
+        #[serde(rename = "@perPage", default)]
+        pub per_page: usize,
+        #[serde(rename = "@totalPages", default)]
+        pub total_pages: usize,
+        #[serde(rename = "@total", default)]
+        pub total: usize,
+        #[serde(rename = "tag", default)]
+        pub tags: Vec<Tag>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+    #[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+    #[serde(rename = "tracks")]
+    pub struct TopTracks {
+        #[serde(rename = "@page")]
+        pub page: usize,
+        #[serde(rename = "@perPage")]
+        pub per_page: usize,
+        #[serde(rename = "@totalPages")]
+        pub total_pages: usize,
+        #[serde(rename = "@total")]
+        pub total: usize,
+        #[serde(rename = "$value")]
+        pub tracks: Vec<Track>,
+    }
+
+    pub type GetTopArtistsResponse = LfmStatus<TopArtists>;
+
+    pub type GetTopTagsResponse = LfmStatus<TopTags>;
+
+    pub type GetTopTracksResponse = LfmStatus<TopTracks>;
+}
+
+/// The charts of a single country, like the global ones in [`chart`].
+pub mod geo {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+    #[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+    #[serde(rename = "artist")]
+    pub struct Artist {
+        #[serde(rename = "@rank", default, deserialize_with = "lenient::option_i64")]
+        pub rank: Option<i64>,
+        pub name: String,
+        /// Only sent for some countries.
+        #[serde(default, deserialize_with = "lenient::option_i64")]
+        pub playcount: Option<i64>,
+        pub listeners: i64,
+        #[serde(default)]
+        pub mbid: String,
+        pub url: Url,
+        #[serde(default)]
+        pub streamable: bool,
+        #[serde(rename = "image", default)]
+        pub images: Vec<Image>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+    #[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+    #[serde(rename = "topartists")]
+    pub struct TopArtists {
+        /// The country as named in ISO 3166-1, e.g. `Germany`.
+        #[serde(rename = "@country")]
+        pub country: String,
+        #[serde(rename = "@page")]
+        pub page: usize,
+        #[serde(rename = "@perPage")]
+        pub per_page: usize,
+        #[serde(rename = "@totalPages")]
+        pub total_pages: usize,
+        #[serde(rename = "@total")]
+        pub total: usize,
+        #[serde(rename = "artist", default)]
+        pub artists: Vec<Artist>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+    #[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+    #[serde(rename = "track")]
+    pub struct Track {
+        #[serde(rename = "@rank", default, deserialize_with = "lenient::option_i64")]
+        pub rank: Option<i64>,
+        pub name: String,
+        /// Length in seconds, 0 if unknown.
+        #[serde(default)]
+        pub duration: i64,
+        pub listeners: i64,
+        #[serde(default, deserialize_with = "lenient::non_empty")]
+        pub mbid: Option<String>,
+        pub url: Url,
+        #[serde(default)]
+        pub streamable: bool,
+        pub artist: ShortArtist,
+        #[serde(rename = "image", default)]
+        pub images: Vec<Image>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+    #[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+    #[serde(rename = "tracks")]
+    pub struct TopTracks {
+        /// The country as named in ISO 3166-1, e.g. `Germany`.
+        #[serde(rename = "@country")]
+        pub country: String,
+        #[serde(rename = "@page")]
+        pub page: usize,
+        #[serde(rename = "@perPage")]
+        pub per_page: usize,
+        #[serde(rename = "@totalPages")]
+        pub total_pages: usize,
+        #[serde(rename = "@total")]
+        pub total: usize,
+        #[serde(rename = "track", default)]
+        pub tracks: Vec<Track>,
+    }
+
+    pub type GetTopArtistsResponse = LfmStatus<TopArtists>;
+
+    pub type GetTopTracksResponse = LfmStatus<TopTracks>;
+}
+
+/// Deserializers tolerating the inconsistencies between endpoints.
+mod lenient {
+    use serde::{Deserialize, Deserializer, de};
+
+    /// An integer that may be given as a number or a (possibly empty) string, or be missing.
+    /// Works for attributes as well as for child elements.
+    pub fn option_i64<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
+
+        struct Text(Option<i64>);
+
+        impl<'de> Deserialize<'de> for Text {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                option_i64(deserializer).map(Text)
+            }
+        }
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Option<i64>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "an integer")
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(Some(v))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                i64::try_from(v).map(Some).map_err(E::custom)
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                match v.trim() {
+                    "" => Ok(None),
+                    v => v.parse().map(Some).map_err(E::custom),
+                }
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            /// A child element, whose text is the number.
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut value = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    if key == "$text" {
+                        value = map.next_value::<Text>()?.0;
+                    } else {
+                        map.next_value::<de::IgnoredAny>()?;
+                    }
+                }
+                Ok(value)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+
+    /// A string that is left empty when missing, like many MusicBrainz ids.
+    pub fn non_empty<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<String>::deserialize(deserializer)?.filter(|s| !s.is_empty()))
+    }
+
+    /// Like [`option_i64`], for counts that cannot be negative.
+    pub fn option_u64<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        option_i64(deserializer)?
+            .map(u64::try_from)
+            .transpose()
+            .map_err(de::Error::custom)
+    }
+}
+
+pub type GetTopAlbumsResponse = LfmStatus<TopAlbums>;
+
+pub type GetTopArtistsResponse = LfmStatus<TopArtists>;
+
+pub type GetTopTracksResponse = LfmStatus<TopTracks>;
+
+pub type GetTagTopAlbumsResponse = LfmStatus<TagTopAlbums>;
+
+pub type GetTagTopArtistsResponse = LfmStatus<TagTopArtists>;
+
+pub type GetTagInfoResponse = LfmStatus<TagInfo>;
+
+pub type GetWeeklyAlbumChartResponse = LfmStatus<WeeklyAlbumChart>;
+
+pub type GetWeeklyChartListResponse = LfmStatus<WeeklyChartList>;
+
+pub type GetRecentTracksResponse = LfmStatus<RecentTracks>;
+
+pub type GetSimilarTracksResponse = LfmStatus<SimilarTracks>;
+
+pub type GetSimilarArtistsResponse = LfmStatus<SimilarArtists>;
+
+pub type GetArtistCorrectionResponse = LfmStatus<ArtistCorrection>;
+
+pub type GetFriendsResponse = LfmStatus<Friends>;
+
+pub type GetUserInfoResponse = LfmStatus<UserSummary>;
+
+pub type GetLovedTracksResponse = LfmStatus<LovedTracks>;
+
+pub type GetPersonalTagAlbumsResponse = LfmStatus<PersonalTagAlbums>;
+
+pub type GetAlbumInfoResponse = LfmStatus<AlbumInfo>;
+
+pub type GetArtistInfoResponse = LfmStatus<ArtistInfo>;
+
+pub type GetTrackInfoResponse = LfmStatus<TrackInfo>;
+
+pub type SearchTracksResponse = LfmStatus<TrackSearchResults>;
+
+pub type SearchArtistsResponse = LfmStatus<ArtistSearchResults>;
+
+pub type SearchAlbumsResponse = LfmStatus<AlbumSearchResults>;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    use quick_xml::de::from_str;
+
+    fn i(size: ImageSize, url: &str) -> Image {
+        Image {
+            size,
+            url: url.into(),
+        }
+    }
+
+    #[test]
+    fn status_failed() {
+        let x: LfmStatus<()> = from_str(
+            r#"
+            <lfm status="failed">
+                <error code="10">Invalid API Key</error>
+            </lfm>
+            "#,
+        )
+        .expect("can parse xml");
+        assert_eq!(
+            x,
+            LfmStatus::Failed(Error {
+                code: "10".into(),
+                message: "Invalid API Key".into(),
+                context: None,
+            })
+        );
+    }
+
+    fn failed(xml: &str) -> Error {
+        from_str::<LfmStatus<Session>>(xml)
+            .expect("can parse xml")
+            .into_result()
+            .unwrap_err()
+    }
+
+    #[test]
+    fn empty_library() {
+        let x: GetTopAlbumsResponse = from_str(
+            r#"<lfm status="ok"><topalbums user="new" type="overall" page="1" perPage="50" totalPages="0" total="0"></topalbums></lfm>"#,
+        )
+        .expect("can parse");
+        let LfmStatus::Ok(albums) = x else {
+            panic!("expected albums, got {x:?}");
+        };
+        assert_eq!(albums.albums, []);
+    }
+
+    #[test]
+    fn write_endpoint_errors() {
+        // track.love with an expired session
+        let error = failed(
+            r#"<lfm status="failed">
+  <error code="9">Invalid session key - Please re-authenticate</error>
+</lfm>"#,
+        );
+        assert_eq!(error.code, "9");
+        assert_eq!(
+            error.message,
+            "Invalid session key - Please re-authenticate"
+        );
+        assert_eq!(error.context, None);
+
+        // track.scrobble while the scrobbling service is down
+        let error = failed(r#"<lfm status="failed"><error code="11"/></lfm>"#);
+        assert_eq!(error.code, "11");
+        assert_eq!(error.message, "");
+
+        // with supplementary context naming the offending parameter
+        let error = failed(
+            r#"<lfm status="failed">
+  <error code="6">Invalid parameters<context>artist</context></error>
+</lfm>"#,
+        );
+        assert_eq!(error.message, "Invalid parameters");
+        assert_eq!(error.context.as_deref(), Some("artist"));
+    }
+
+    #[test]
+    fn multiple_errors() {
+        let error = failed(
+            r#"<lfm status="failed">
+  <error code="9">Invalid session key - Please re-authenticate</error>
+  <error code="6">Track not found</error>
+</lfm>"#,
+        );
+        assert_eq!(error.code, "9");
+        assert_eq!(error.context.as_deref(), Some("6: Track not found"));
+        assert!(error.to_string().ends_with("(6: Track not found)"));
+    }
+
+    #[test]
+    fn auth_get_session_response() {
+        let x: AuthGetSessionResponse = from_str(
+            r#"<lfm status="ok">
+                <session>
+                <name>MyLastFMUsername</name>
+                <key>d580d57f32848f5dcf574d1ce18d78b2</key>
+                <subscriber>0</subscriber>
+                </session>
+            </lfm>"#,
+        )
+        .expect("can parse");
+        assert_eq!(
+            x,
+            AuthGetSessionResponse(LfmStatus::Ok(Session {
+                name: "MyLastFMUsername".into(),
+                key: "d580d57f32848f5dcf574d1ce18d78b2".into(),
+                subscriber: 0
+            }))
+        );
+    }
+
+    const TOP_ALBUMS: &str = r#"<topalbums user="RJ" type="overall" page="1" perPage="50" totalPages="1" total="1">
+<album rank="1">
+  <name>Images and Words</name>
+  <playcount>174</playcount>
+  <mbid>f20971f2-c8ad-4d26-91ab-730f6dedafb2</mbid>  
+  <url>
+    http://www.last.fm/music/Dream+Theater/Images+and+Words
+  </url>
+  <artist>
+    <name>Dream Theater</name>
+    <mbid>28503ab7-8bf2-4666-a7bd-2644bfc7cb1d</mbid>
+    <url>http://www.last.fm/music/Dream+Theater</url>
+  </artist>
+  <image size="small">...</image>
+  <image size="medium">...</image>
+  <image size="large">...</image>
+</album>
+</topalbums>"#;
+
+    #[test]
+    fn user_get_top_albums_response() {
+        let x: TopAlbums = from_str(TOP_ALBUMS).expect("can parse");
+        assert_eq!(
+            x,
+            TopAlbums {
+                user: "RJ".into(),
+                period: Some(Period::Overall),
+                page: 1,
+                per_page: 50,
+                total_pages: 1,
+                total: 1,
+                albums: vec![Album {
+                    rank: Some(1),
+                    name: "Images and Words".into(),
+                    playcount: 174,
+                    mbid: "f20971f2-c8ad-4d26-91ab-730f6dedafb2".into(),
+                    url: "http://www.last.fm/music/Dream+Theater/Images+and+Words"
+                        .parse()
+                        .unwrap(),
+                    artist: ShortArtist {
+                        name: "Dream Theater".into(),
+                        mbid: "28503ab7-8bf2-4666-a7bd-2644bfc7cb1d".into(),
+                        url: "http://www.last.fm/music/Dream+Theater".parse().unwrap(),
+                        images: Vec::new(),
+                    },
+                    images: vec![
+                        i(ImageSize::Small, "..."),
+                        i(ImageSize::Medium, "..."),
+                        i(ImageSize::Large, "..."),
+                    ]
+                }]
+            }
+        );
+    }
+
+    const TOP_ARTISTS: &str = r#"<topartists user="RJ" type="overall">
+  <artist rank="1">
+    <name>Dream Theater</name>
+    <playcount>1337</playcount>
+    <mbid>28503ab7-8bf2-4666-a7bd-2644bfc7cb1d</mbid>
+    <url>http://www.last.fm/music/Dream+Theater</url>
+    <streamable>1</streamable>
+    <image size="small">...</image>
+    <image size="medium">...</image>
+    <image size="large">...</image>
+  </artist>
+</topartists>"#;
+
+    const TOP_TRACKS: &str = r#"<toptracks user="RJ" type="overall" page="1" perPage="1" totalPages="250" total="250">
+  <track rank="1">
+    <name>Learning to Live</name>
+    <duration>691</duration>
+    <playcount>42</playcount>
+    <mbid>b0b0ed8c-8d3b-4b30-9b9a-5c7f0d5d2b1e</mbid>
+    <url>https://www.last.fm/music/Dream+Theater/_/Learning+to+Live</url>
+    <streamable fulltrack="0">0</streamable>
+    <artist>
+      <name>Dream Theater</name>
+      <mbid>28503ab7-8bf2-4666-a7bd-2644bfc7cb1d</mbid>
+      <url>https://www.last.fm/music/Dream+Theater</url>
+    </artist>
+    <image size="small">https://example.com/small.png</image>
+    <image size="medium">https://example.com/medium.png</image>
+  </track>
+</toptracks>"#;
+
+    #[test]
+    fn user_get_top_tracks() {
+        let x: TopTracks = from_str(TOP_TRACKS).expect("can parse");
+        assert_eq!(
+            x,
+            TopTracks {
+                user: "RJ".into(),
+                period: Some(Period::Overall),
+                page: 1,
+                per_page: 1,
+                total_pages: 250,
+                total: 250,
+                tracks: vec![Track {
+                    rank: Some(1),
+                    name: "Learning to Live".into(),
+                    duration: 691,
+                    playcount: 42,
+                    mbid: "b0b0ed8c-8d3b-4b30-9b9a-5c7f0d5d2b1e".into(),
+                    url: "https://www.last.fm/music/Dream+Theater/_/Learning+to+Live"
+                        .parse()
+                        .unwrap(),
+                    streamable: false,
+                    artist: ShortArtist {
+                        name: "Dream Theater".into(),
+                        mbid: "28503ab7-8bf2-4666-a7bd-2644bfc7cb1d".into(),
+                        url: "https://www.last.fm/music/Dream+Theater".parse().unwrap(),
+                        images: Vec::new(),
+                    },
+                    images: vec![
+                        i(ImageSize::Small, "https://example.com/small.png"),
+                        i(ImageSize::Medium, "https://example.com/medium.png"),
+                    ]
+                }]
+            }
+        )
+    }
+
+    #[test]
+    fn user_get_top_artists() {
+        let x: TopArtists = from_str(TOP_ARTISTS).expect("can parse");
+        assert_eq!(
+            x,
+            TopArtists {
+                user: "RJ".into(),
+                period: Some(Period::Overall),
+                page: 0,
+                per_page: 0,
+                total_pages: 0,
+                total: 0,
+                artists: vec![Artist {
+                    rank: Some(1),
+                    name: "Dream Theater".into(),
+                    playcount: 1337,
+                    mbid: "28503ab7-8bf2-4666-a7bd-2644bfc7cb1d".into(),
+                    url: "http://www.last.fm/music/Dream+Theater".parse().unwrap(),
+                    streamable: true,
+                    images: vec![
+                        i(ImageSize::Small, "..."),
+                        i(ImageSize::Medium, "..."),
+                        i(ImageSize::Large, "..."),
+                    ]
+                }]
+            }
+        )
+    }
+
+    #[test]
+    fn display_period() {
+        assert_eq!(Period::Overall.to_string(), "overall");
+    }
+
+    #[test]
+    fn parse_period() {
+        assert_eq!("1month".parse(), Ok(Period::OneMonth));
+        assert_eq!(
+            "fortnight".parse::<Period>(),
+            Err(ParsePeriodError("fortnight".into()))
+        );
+    }
+
+    #[test]
+    fn largest_image() {
+        let album: Album = from_str(
+            r#"<album rank="1">
+                <name>Vector</name>
+                <playcount>12</playcount>
+                <mbid></mbid>
+                <url>https://www.last.fm/music/Haken/Vector</url>
+                <artist>
+                    <name>Haken</name>
+                    <mbid></mbid>
+                    <url>https://www.last.fm/music/Haken</url>
+                </artist>
+                <image size="small">https://example.com/small.png</image>
+                <image size="extralarge">https://example.com/extralarge.png</image>
+                <image size="medium">https://example.com/medium.png</image>
+                <image size="mega"></image>
+            </album>"#,
+        )
+        .expect("can parse");
+        assert_eq!(
+            largest(&album.images).map(|image| image.url.as_str()),
+            Some("https://example.com/extralarge.png")
+        );
+        assert_eq!(largest(&[]), None);
+    }
+
+    /// Parsing, serializing and parsing again must give back the same value, otherwise the types
+    /// silently drop data.
+    fn assert_round_trip<T>(xml: &str)
+    where
+        T: Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+    {
+        let parsed: T = from_str(xml).expect("can parse");
+        let serialized = quick_xml::se::to_string(&parsed).expect("can serialize");
+        let reparsed: T = from_str(&serialized).expect("can parse serialized");
+        assert_eq!(parsed, reparsed);
+    }
+
+    #[test]
+    fn fixtures_round_trip() {
+        assert_round_trip::<TopAlbums>(TOP_ALBUMS);
+        assert_round_trip::<TopArtists>(TOP_ARTISTS);
+        assert_round_trip::<TopTracks>(TOP_TRACKS);
+    }
+
+    #[cfg(feature = "strict")]
+    #[test]
+    fn strict_rejects_unknown_fields() {
+        let drifted = TOP_ARTISTS.replace(r#"rank="1""#, r#"rank="1" ontour="0""#);
+        assert!(from_str::<TopArtists>(&drifted).is_err());
+    }
+
+    #[test]
+    fn lenient_rank() {
+        let artist = |attrs: &str| {
+            from_str::<Artist>(&format!(
+                r#"<artist{attrs}>
+    <name>Dream Theater</name>
+    <playcount>1337</playcount>
+    <mbid></mbid>
+    <url>http://www.last.fm/music/Dream+Theater</url>
+    <streamable>1</streamable>
+    <image size="small">...</image>
+</artist>"#
+            ))
+            .expect("can parse")
+            .rank
+        };
+        assert_eq!(artist(r#" rank="7""#), Some(7));
+        assert_eq!(artist(r#" rank=" 7 ""#), Some(7));
+        assert_eq!(artist(r#" rank="""#), None);
+        assert_eq!(artist(""), None);
+    }
+
+    #[test]
+    fn rank_attribute_or_child() {
+        let track = |attrs: &str, rank: &str| {
+            from_str::<Track>(&format!(
+                r#"<track{attrs}>
+    {rank}
+    <name>Puzzle Box</name>
+    <playcount>42</playcount>
+    <mbid></mbid>
+    <url>https://www.last.fm/music/Haken/_/Puzzle+Box</url>
+    <streamable>0</streamable>
+    <artist>
+      <name>Haken</name>
+      <mbid></mbid>
+      <url>https://www.last.fm/music/Haken</url>
+    </artist>
+    <image size="small"></image>
+</track>"#
+            ))
+            .expect("can parse")
+        };
+        let attribute = track(r#" rank="3""#, "");
+        let child = track("", "<rank>3</rank>");
+        assert_eq!(attribute.rank, Some(3));
+        assert_eq!(child, attribute);
+        assert_eq!(track("", "<rank> 3 </rank>").rank, Some(3));
+        assert_eq!(track("", "<rank></rank>").rank, None);
+        assert_eq!(track("", "<rank/>").rank, None);
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Tagged {
+        #[serde(alias = "toptags", default)]
+        tags: Tags,
+    }
+
+    fn tag_names(xml: &str) -> Vec<String> {
+        from_str::<Tagged>(xml)
+            .expect("can parse")
+            .tags
+            .tags
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect()
+    }
+
+    #[test]
+    fn populated_tags() {
+        let names = tag_names(
+            r#"<album>
+  <tags>
+    <tag>
+      <name>progressive metal</name>
+      <url>https://www.last.fm/tag/progressive+metal</url>
+    </tag>
+    <tag>
+      <name>progressive rock</name>
+      <url>https://www.last.fm/tag/progressive+rock</url>
+    </tag>
+  </tags>
+</album>"#,
+        );
+        assert_eq!(names, ["progressive metal", "progressive rock"]);
+    }
+
+    #[test]
+    fn single_top_tag() {
+        let xml = r#"<track>
+  <toptags>
+    <tag>
+      <name>djent</name>
+      <url>https://www.last.fm/tag/djent</url>
+      <count>100</count>
+    </tag>
+  </toptags>
+</track>"#;
+        assert_eq!(tag_names(xml), ["djent"]);
+        let tagged: Tagged = from_str(xml).unwrap();
+        assert_eq!(tagged.tags.tags[0].count, Some(100));
+    }
+
+    #[test]
+    fn empty_tags() {
+        assert_eq!(tag_names("<album><tags/></album>"), Vec::<String>::new());
+        assert_eq!(
+            tag_names("<album><tags>\n</tags></album>"),
+            Vec::<String>::new()
+        );
+        assert_eq!(tag_names("<album></album>"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn user_get_friends() {
+        let x: GetFriendsResponse = from_str(
+            r#"<lfm status="ok">
+<friends for="RJ" page="1" perPage="50" totalPages="1" total="2">
+  <user>
+    <name>eartle</name>
+    <realname>Michael Coffey</realname>
+    <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/eartle.png</image>
+    <image size="large">https://lastfm.freetls.fastly.net/i/u/174s/eartle.png</image>
+    <url>https://www.last.fm/user/eartle</url>
+    <country>United Kingdom</country>
+    <subscriber>1</subscriber>
+    <playcount>123456</playcount>
+    <registered unixtime="1187185980">2007-08-15 13:53</registered>
+  </user>
+  <user>
+    <name>quiet</name>
+    <realname></realname>
+    <image size="small"></image>
+    <url>https://www.last.fm/user/quiet</url>
+  </user>
+</friends>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let LfmStatus::Ok(friends) = x else {
+            panic!("expected friends, got {x:?}");
+        };
+        assert_eq!(friends.user, "RJ");
+        assert_eq!(friends.total, 2);
+        assert_eq!(
+            friends.friends[0],
+            UserSummary {
+                name: "eartle".into(),
+                realname: "Michael Coffey".into(),
+                url: "https://www.last.fm/user/eartle".parse().unwrap(),
+                country: "United Kingdom".into(),
+                subscriber: true,
+                playcount: 123456,
+                registered: Some(Registered {
+                    unixtime: 1187185980,
+                    text: "2007-08-15 13:53".into(),
+                }),
+                images: vec![
+                    i(
+                        ImageSize::Small,
+                        "https://lastfm.freetls.fastly.net/i/u/34s/eartle.png"
+                    ),
+                    i(
+                        ImageSize::Large,
+                        "https://lastfm.freetls.fastly.net/i/u/174s/eartle.png"
+                    ),
+                ],
+                age: None,
+                gender: None,
+                playlists: None,
+                bootstrap: None,
+                account_type: None,
+                artist_count: None,
+                album_count: None,
+                track_count: None,
+            }
+        );
+        assert_eq!(friends.friends[1].name, "quiet");
+        assert_eq!(friends.friends[1].realname, "");
+        assert_eq!(friends.friends[1].registered, None);
+    }
+
+    #[test]
+    fn user_get_weekly_chart_list() {
+        let x: GetWeeklyChartListResponse = from_str(
+            r#"<lfm status="ok">
+<weeklychartlist user="RJ">
+  <chart from="1108296000" to="1108900800"/>
+  <chart from="1108900800" to="1109505600" />
+  <chart from="1109505600" to="1110110400"></chart>
+</weeklychartlist>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let LfmStatus::Ok(list) = x else {
+            panic!("expected a chart list, got {x:?}");
+        };
+        assert_eq!(list.user, "RJ");
+        assert_eq!(
+            list.charts,
+            [
+                ChartRange {
+                    from: 1108296000,
+                    to: 1108900800
+                },
+                ChartRange {
+                    from: 1108900800,
+                    to: 1109505600
+                },
+                ChartRange {
+                    from: 1109505600,
+                    to: 1110110400
+                },
+            ]
+        );
+
+        let empty: GetWeeklyChartListResponse =
+            from_str(r#"<lfm status="ok"><weeklychartlist user="new"/></lfm>"#).expect("can parse");
+        assert_eq!(empty.into_result().unwrap().charts, []);
+    }
+
+    #[test]
+    fn user_get_weekly_album_chart() {
+        let x: GetWeeklyAlbumChartResponse = from_str(
+            r#"<lfm status="ok">
+<weeklyalbumchart user="RJ" from="1114965332" to="1115570132">
+  <album rank="1">
+    <artist mbid="a74b1b7f-71a5-4011-9441-d0b5e4122711">Radiohead</artist>
+    <name>OK Computer</name>
+    <mbid>0b6b4ba0-d36f-47bd-b4ea-6a5b91842d29</mbid>
+    <playcount>12</playcount>
+    <url>https://www.last.fm/music/Radiohead/OK+Computer</url>
+  </album>
+  <album rank="2">
+    <artist mbid="">Dream Theater</artist>
+    <name>Images and Words</name>
+    <mbid></mbid>
+    <playcount>3</playcount>
+    <url>https://www.last.fm/music/Dream+Theater/Images+and+Words</url>
+  </album>
+</weeklyalbumchart>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let LfmStatus::Ok(chart) = x else {
+            panic!("expected a chart, got {x:?}");
+        };
+        assert_eq!((chart.from, chart.to), (1114965332, 1115570132));
+        assert_eq!(
+            chart.albums[0],
+            WeeklyAlbum {
+                rank: 1,
+                artist: ChartArtist {
+                    mbid: "a74b1b7f-71a5-4011-9441-d0b5e4122711".into(),
+                    name: "Radiohead".into(),
+                },
+                name: "OK Computer".into(),
+                mbid: "0b6b4ba0-d36f-47bd-b4ea-6a5b91842d29".into(),
+                playcount: 12,
+                url: "https://www.last.fm/music/Radiohead/OK+Computer"
+                    .parse()
+                    .unwrap(),
+            }
+        );
+        assert_eq!(chart.albums[1].artist.mbid, "");
+        assert_eq!(chart.albums[1].artist.name, "Dream Theater");
+    }
+
+    #[test]
+    fn user_get_loved_tracks() {
+        let x: GetLovedTracksResponse = from_str(
+            r#"<lfm status="ok">
+<lovedtracks user="RJ" page="1" perPage="2" totalPages="183" total="365">
+  <track>
+    <name>Puzzle Box</name>
+    <mbid></mbid>
+    <url>https://www.last.fm/music/Haken/_/Puzzle+Box</url>
+    <date uts="1541761200">09 Nov 2018, 11:00</date>
+    <artist>
+      <name>Haken</name>
+      <mbid>cc0b7089-c08d-4c10-b6b0-873582c17fd6</mbid>
+      <url>https://www.last.fm/music/Haken</url>
+    </artist>
+    <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/vector.png</image>
+    <streamable fulltrack="0">0</streamable>
+  </track>
+  <track>
+    <name>Believe</name>
+    <mbid>8a0d5e2f-2d2d-4c50-9e3b-6f1f0c2d3e4a</mbid>
+    <url>https://www.last.fm/music/Cher/_/Believe</url>
+    <date uts="1222800480">30 Sep 2008, 18:48</date>
+    <artist>
+      <name>Cher</name>
+      <mbid>bfcc6d75-a6a5-4bc6-8282-47aec8531818</mbid>
+      <url>https://www.last.fm/music/Cher</url>
+    </artist>
+    <image size="small"></image>
+    <streamable fulltrack="0">0</streamable>
+  </track>
+</lovedtracks>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let LfmStatus::Ok(loved) = x else {
+            panic!("expected loved tracks, got {x:?}");
+        };
+        assert_eq!(loved.user, "RJ");
+        assert_eq!(loved.total_pages, 183);
+        assert_eq!(loved.total, 365);
+        let tracks: Vec<_> = loved
+            .tracks
+            .iter()
+            .map(|track| {
+                (
+                    track.artist.name.as_str(),
+                    track.name.as_str(),
+                    track.date.uts,
+                )
+            })
+            .collect();
+        assert_eq!(
+            tracks,
+            [
+                ("Haken", "Puzzle Box", 1541761200),
+                ("Cher", "Believe", 1222800480)
+            ]
+        );
+        assert_round_trip::<LovedTracks>(&quick_xml::se::to_string(&loved).unwrap());
+    }
+
+    #[test]
+    fn friend_and_profile_share_user_type() {
+        let user = r#"<user>
+    <name>eartle</name>
+    <realname>Michael Coffey</realname>
+    <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/eartle.png</image>
+    <url>https://www.last.fm/user/eartle</url>
+    <country>United Kingdom</country>
+    <subscriber>1</subscriber>
+    <playcount>123456</playcount>
+    <registered unixtime="1187185980">2007-08-15 13:53</registered>
+  </user>"#;
+        let friends: GetFriendsResponse = from_str(&format!(
+            r#"<lfm status="ok"><friends for="RJ" page="1" perPage="50" totalPages="1" total="1">{user}</friends></lfm>"#
+        ))
+        .expect("can parse");
+        let friend: UserSummary = friends.into_result().unwrap().friends.remove(0);
+
+        let profile = user.replace(
+            "</user>",
+            "<age>0</age><gender>n</gender><playlists>0</playlists><bootstrap>0</bootstrap>\
+             <type>user</type><artist_count>12</artist_count></user>",
+        );
+        let info: GetUserInfoResponse =
+            from_str(&format!(r#"<lfm status="ok">{profile}</lfm>"#)).expect("can parse");
+        let profile: UserSummary = info.into_result().unwrap();
+
+        assert_eq!(friend.artist_count, None);
+        assert_eq!(profile.artist_count, Some(12));
+        assert_eq!(
+            UserSummary {
+                age: None,
+                gender: None,
+                playlists: None,
+                bootstrap: None,
+                account_type: None,
+                artist_count: None,
+                ..profile
+            },
+            friend
+        );
+    }
+
+    #[test]
+    fn user_get_info() {
+        let x: GetUserInfoResponse = from_str(
+            r#"<lfm status="ok">
+<user>
+  <name>RJ</name>
+  <realname>Richard Jones</realname>
+  <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/rj.png</image>
+  <image size="large">https://lastfm.freetls.fastly.net/i/u/174s/rj.png</image>
+  <url>https://www.last.fm/user/RJ</url>
+  <country>United Kingdom</country>
+  <age>0</age>
+  <gender>n</gender>
+  <subscriber>1</subscriber>
+  <playcount>150316</playcount>
+  <playlists>0</playlists>
+  <bootstrap>0</bootstrap>
+  <registered unixtime="1037793040">2002-11-20 11:50</registered>
+  <type>alum</type>
+  <artist_count>5312</artist_count>
+  <album_count>11045</album_count>
+  <track_count>32901</track_count>
+</user>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let LfmStatus::Ok(user) = x else {
+            panic!("expected a user, got {x:?}");
+        };
+        assert_eq!(user.name, "RJ");
+        assert_eq!(user.realname, "Richard Jones");
+        assert_eq!(user.country, "United Kingdom");
+        assert!(user.subscriber);
+        assert_eq!(user.playcount, 150316);
+        assert_eq!(
+            user.registered,
+            Some(Registered {
+                unixtime: 1037793040,
+                text: "2002-11-20 11:50".into(),
+            })
+        );
+        assert_eq!(user.account_type.as_deref(), Some("alum"));
+        assert_eq!(user.album_count, Some(11045));
+        assert_eq!(
+            largest(&user.images),
+            Some(&i(
+                ImageSize::Large,
+                "https://lastfm.freetls.fastly.net/i/u/174s/rj.png"
+            ))
+        );
+    }
+
+    #[test]
+    fn user_without_friends() {
+        let x: GetFriendsResponse = from_str(
+            r#"<lfm status="ok"><friends for="loner" page="1" perPage="50" totalPages="0" total="0"/></lfm>"#,
+        )
+        .expect("can parse");
+        let LfmStatus::Ok(friends) = x else {
+            panic!("expected friends, got {x:?}");
+        };
+        assert_eq!(friends.friends, []);
+    }
+
+    #[test]
+    fn track_get_similar() {
+        let x: GetSimilarTracksResponse = from_str(
+            r#"<lfm status="ok">
+<similartracks track="Believe" artist="Cher">
+  <track>
+    <name>Strong Enough</name>
+    <playcount>1151930</playcount>
+    <mbid>7d15c3a5-6f45-4b2d-9f1e-5b4b0e6a4b58</mbid>
+    <match>1</match>
+    <url>https://www.last.fm/music/Cher/_/Strong+Enough</url>
+    <streamable fulltrack="0">0</streamable>
+    <duration>223</duration>
+    <artist>
+      <name>Cher</name>
+      <mbid>bfcc6d75-a6a5-4bc6-8282-47aec8531818</mbid>
+      <url>https://www.last.fm/music/Cher</url>
+    </artist>
+    <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/strong.png</image>
+  </track>
+  <track>
+    <name>Ray of Light</name>
+    <playcount>896312</playcount>
+    <match>0.417852</match>
+    <url>https://www.last.fm/music/Madonna/_/Ray+of+Light</url>
+    <streamable fulltrack="0">0</streamable>
+    <artist>
+      <name>Madonna</name>
+      <mbid>79239441-bfd5-4981-a70c-55c3f15c1287</mbid>
+      <url>https://www.last.fm/music/Madonna</url>
+    </artist>
+    <image size="small"></image>
+  </track>
+</similartracks>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let LfmStatus::Ok(similar) = x else {
+            panic!("expected similar tracks, got {x:?}");
+        };
+        assert_eq!(similar.track, "Believe");
+        assert_eq!(similar.artist, "Cher");
+        let scores: Vec<_> = similar
+            .tracks
+            .iter()
+            .map(|track| (track.name.as_str(), track.match_score))
+            .collect();
+        assert_eq!(scores, [("Strong Enough", 1.0), ("Ray of Light", 0.417852)]);
+        assert_eq!(
+            similar.tracks[0].mbid.as_deref(),
+            Some("7d15c3a5-6f45-4b2d-9f1e-5b4b0e6a4b58")
+        );
+        assert_eq!(similar.tracks[1].mbid, None);
+        assert_eq!(similar.tracks[1].duration, 0);
+        assert_eq!(similar.tracks[1].artist.name, "Madonna");
+    }
+
+    #[test]
+    fn artist_get_similar() {
+        let x: GetSimilarArtistsResponse = from_str(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<lfm status="ok">
+  <similarartists artist="Haken">
+    <artist>
+      <name>Leprous</name>
+      <mbid>a5b29dbb-d1a6-4f6b-ac7e-51a3a2e9c2a8</mbid>
+      <match>1</match>
+      <url>https://www.last.fm/music/Leprous</url>
+      <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/leprous.png</image>
+      <image size="medium">https://lastfm.freetls.fastly.net/i/u/64s/leprous.png</image>
+      <streamable>0</streamable>
+    </artist>
+    <artist>
+      <name>Caligula's Horse</name>
+      <match>0.872314</match>
+      <url>https://www.last.fm/music/Caligula%27s+Horse</url>
+      <image size="small"></image>
+      <streamable>0</streamable>
+    </artist>
+  </similarartists>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let similar = x.into_result().unwrap();
+        assert_eq!(similar.artist, "Haken");
+        assert_eq!(
+            similar.artists[0],
+            SimilarArtist {
+                name: "Leprous".into(),
+                mbid: Some("a5b29dbb-d1a6-4f6b-ac7e-51a3a2e9c2a8".into()),
+                match_score: 1.0,
+                url: "https://www.last.fm/music/Leprous".parse().unwrap(),
+                images: vec![
+                    i(
+                        ImageSize::Small,
+                        "https://lastfm.freetls.fastly.net/i/u/34s/leprous.png"
+                    ),
+                    i(
+                        ImageSize::Medium,
+                        "https://lastfm.freetls.fastly.net/i/u/64s/leprous.png"
+                    ),
+                ],
+                streamable: false,
+            }
+        );
+        let second = &similar.artists[1];
+        assert_eq!(second.name, "Caligula's Horse");
+        assert_eq!(second.mbid, None);
+        assert_eq!(second.match_score, 0.872314);
+    }
+
+    #[test]
+    fn user_get_recent_tracks() {
+        let x: GetRecentTracksResponse = from_str(
+            r#"<lfm status="ok">
+<recenttracks user="RJ" page="1" perPage="2" totalPages="4096" total="8192">
+  <track nowplaying="true">
+    <artist mbid="cc0b7089-c08d-4c10-b6b0-873582c17fd6">Haken</artist>
+    <name>Puzzle Box</name>
+    <streamable>0</streamable>
+    <mbid></mbid>
+    <album mbid="">Vector</album>
+    <url>https://www.last.fm/music/Haken/_/Puzzle+Box</url>
+    <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/vector.png</image>
+  </track>
+  <track>
+    <artist mbid="cc0b7089-c08d-4c10-b6b0-873582c17fd6">Haken</artist>
+    <name>The Good Doctor</name>
+    <streamable>0</streamable>
+    <mbid>3b3a5e9f-1c2d-4f7e-9a45-0e5a2d1f6c88</mbid>
+    <album mbid="d6a1b5c4-2f3e-4a8b-9c7d-1e0f2a3b4c5d">Vector</album>
+    <url>https://www.last.fm/music/Haken/_/The+Good+Doctor</url>
+    <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/vector.png</image>
+    <date uts="1213031819">9 Jun 2008, 17:16</date>
+  </track>
+</recenttracks>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let LfmStatus::Ok(recent) = x else {
+            panic!("expected recent tracks, got {x:?}");
+        };
+        assert_eq!(recent.total, 8192);
+        let [playing, scrobbled] = &recent.tracks[..] else {
+            panic!("expected two tracks, got {:?}", recent.tracks);
+        };
+        assert!(playing.now_playing);
+        assert_eq!(playing.date, None);
+        assert_eq!(playing.artist.name, "Haken");
+        assert_eq!(playing.artist.mbid, "cc0b7089-c08d-4c10-b6b0-873582c17fd6");
+        assert!(!scrobbled.now_playing);
+        assert_eq!(scrobbled.album.name, "Vector");
+        assert_eq!(
+            scrobbled.date,
+            Some(ScrobbleDate {
+                uts: 1213031819,
+                text: "9 Jun 2008, 17:16".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn extended_recent_tracks() {
+        let x: GetRecentTracksResponse = from_str(
+            r#"<lfm status="ok">
+<recenttracks user="RJ" page="1" perPage="1" totalPages="8192" total="8192">
+  <track>
+    <artist>
+      <url>https://www.last.fm/music/Haken</url>
+      <name>Haken</name>
+      <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/haken.png</image>
+      <mbid>cc0b7089-c08d-4c10-b6b0-873582c17fd6</mbid>
+    </artist>
+    <loved>1</loved>
+    <name>The Good Doctor</name>
+    <streamable>0</streamable>
+    <mbid></mbid>
+    <album mbid="">Vector</album>
+    <url>https://www.last.fm/music/Haken/_/The+Good+Doctor</url>
+    <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/vector.png</image>
+    <date uts="1213031819">9 Jun 2008, 17:16</date>
+  </track>
+</recenttracks>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let track = x.into_result().unwrap().tracks.remove(0);
+        assert!(track.loved);
+        assert_eq!(
+            track.artist,
+            RecentArtist {
+                name: "Haken".into(),
+                mbid: "cc0b7089-c08d-4c10-b6b0-873582c17fd6".into(),
+                url: Some("https://www.last.fm/music/Haken".parse().unwrap()),
+                images: vec![i(
+                    ImageSize::Small,
+                    "https://lastfm.freetls.fastly.net/i/u/34s/haken.png"
+                )],
+            }
+        );
+    }
+
+    #[test]
+    fn user_get_personal_tag_albums() {
+        let x: GetPersonalTagAlbumsResponse = from_str(
+            r#"<lfm status="ok">
+<taggings user="RJ" tag="prog" page="1" perPage="50" totalPages="1" total="2">
+  <albums>
+    <album>
+      <name>Vector</name>
+      <mbid></mbid>
+      <url>https://www.last.fm/music/Haken/Vector</url>
+      <artist>
+        <name>Haken</name>
+        <mbid>9a1e0a34-0cd5-4e3b-8a6c-5b4b4c4a0f7c</mbid>
+        <url>https://www.last.fm/music/Haken</url>
+      </artist>
+      <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/vector.png</image>
+      <image size="large"></image>
+    </album>
+    <album>
+      <name>Images and Words</name>
+      <mbid>d2b3a5a1-fd3c-4b5f-9c3a-2d6e3e0a7c0e</mbid>
+      <url>https://www.last.fm/music/Dream+Theater/Images+and+Words</url>
+      <artist>
+        <name>Dream Theater</name>
+        <mbid>28503ab7-8bf2-4666-a7bd-2644bfc7cb1d</mbid>
+        <url>https://www.last.fm/music/Dream+Theater</url>
+      </artist>
+    </album>
+  </albums>
+</taggings>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let tagged = x.into_result().unwrap();
+        assert_eq!((tagged.user.as_str(), tagged.tag.as_str()), ("RJ", "prog"));
+        assert_eq!(tagged.total, 2);
+        let albums: Vec<_> = tagged
+            .albums
+            .albums
+            .iter()
+            .map(|album| (album.artist.name.as_str(), album.name.as_str()))
+            .collect();
+        assert_eq!(
+            albums,
+            [("Haken", "Vector"), ("Dream Theater", "Images and Words")]
+        );
+
+        for empty in [
+            r#"<lfm status="ok"><taggings user="RJ" tag="polka" page="1" perPage="50" totalPages="0" total="0"><albums></albums></taggings></lfm>"#,
+            r#"<lfm status="ok"><taggings user="RJ" tag="polka" page="1" perPage="50" totalPages="0" total="0"></taggings></lfm>"#,
+        ] {
+            let x: GetPersonalTagAlbumsResponse = from_str(empty).expect("can parse");
+            assert!(x.into_result().unwrap().albums.albums.is_empty());
+        }
+    }
+
+    #[test]
+    fn tag_get_top_albums() {
+        let x: GetTagTopAlbumsResponse = from_str(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<lfm status="ok">
+  <albums tag="progressive metal" page="1" perPage="2" totalPages="20937" total="41873">
+    <album rank="1">
+      <name>Images and Words</name>
+      <mbid>f20971f2-c8ad-4d26-91ab-730f6dedafb2</mbid>
+      <url>https://www.last.fm/music/Dream+Theater/Images+and+Words</url>
+      <artist>
+        <name>Dream Theater</name>
+        <mbid>28503ab7-8bf2-4666-a7bd-2644bfc7cb1d</mbid>
+        <url>https://www.last.fm/music/Dream+Theater</url>
+      </artist>
+      <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/iaw.png</image>
+      <image size="extralarge">https://lastfm.freetls.fastly.net/i/u/300x300/iaw.png</image>
+    </album>
+    <album rank="2">
+      <name>Vector</name>
+      <mbid></mbid>
+      <url>https://www.last.fm/music/Haken/Vector</url>
+      <artist>
+        <name>Haken</name>
+        <mbid></mbid>
+        <url>https://www.last.fm/music/Haken</url>
+      </artist>
+      <image size="small"></image>
+    </album>
+  </albums>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let top = x.into_result().unwrap();
+        assert_eq!(top.tag, "progressive metal");
+        assert_eq!((top.page, top.total), (1, 41873));
+        let albums: Vec<_> = top
+            .albums
+            .iter()
+            .map(|album| (album.rank, album.artist.name.as_str(), album.name.as_str()))
+            .collect();
+        assert_eq!(
+            albums,
+            [
+                (Some(1), "Dream Theater", "Images and Words"),
+                (Some(2), "Haken", "Vector")
+            ]
+        );
+        assert_eq!(top.albums[0].images.len(), 2);
+    }
+
+    #[test]
+    fn tag_get_top_artists() {
+        let x: GetTagTopArtistsResponse = from_str(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<lfm status="ok">
+  <topartists tag="progressive metal" page="1" perPage="2" totalPages="4863" total="9726">
+    <artist rank="1">
+      <name>Dream Theater</name>
+      <mbid>28503ab7-8bf2-4666-a7bd-2644bfc7cb1d</mbid>
+      <url>https://www.last.fm/music/Dream+Theater</url>
+      <streamable>0</streamable>
+      <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/dt.png</image>
+      <image size="medium">https://lastfm.freetls.fastly.net/i/u/64s/dt.png</image>
+    </artist>
+    <artist rank="2">
+      <name>Haken</name>
+      <mbid></mbid>
+      <url>https://www.last.fm/music/Haken</url>
+      <streamable>0</streamable>
+      <image size="small"></image>
+    </artist>
+  </topartists>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let top = x.into_result().unwrap();
+        assert_eq!(top.tag, "progressive metal");
+        assert_eq!((top.per_page, top.total_pages, top.total), (2, 4863, 9726));
+        let artists: Vec<_> = top
+            .artists
+            .iter()
+            .map(|artist| (artist.rank, artist.name.as_str()))
+            .collect();
+        assert_eq!(artists, [(Some(1), "Dream Theater"), (Some(2), "Haken")]);
+        assert_eq!(top.artists[0].images.len(), 2);
+    }
+
+    #[test]
+    fn tag_get_info() {
+        let x: GetTagInfoResponse = from_str(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<lfm status="ok">
+  <tag>
+    <name>disco</name>
+    <total>157046</total>
+    <reach>23476</reach>
+    <wiki>
+      <summary>Disco is a genre of dance music that originated in the early 1970s. &lt;a href="http://www.last.fm/tag/disco"&gt;Read more on Last.fm&lt;/a&gt;.</summary>
+      <content>Disco is a genre of dance music that originated in the early 1970s.</content>
+    </wiki>
+  </tag>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let tag = x.into_result().unwrap();
+        assert_eq!(
+            tag,
+            TagInfo {
+                name: "disco".into(),
+                url: None,
+                total: 157046,
+                reach: 23476,
+                wiki: Some(chart::Wiki {
+                    published: String::new(),
+                    summary: "Disco is a genre of dance music that originated in the early 1970s. <a href=\"http://www.last.fm/tag/disco\">Read more on Last.fm</a>.".into(),
+                    content: "Disco is a genre of dance music that originated in the early 1970s.".into(),
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn track_search() {
+        let x: SearchTracksResponse = from_str(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<lfm status="ok">
+  <results for="Believe" xmlns:opensearch="http://a9.com/-/spec/opensearch/1.1/">
+    <opensearch:Query role="request" searchTerms="Believe" startPage="2" />
+    <opensearch:totalResults>193591</opensearch:totalResults>
+    <opensearch:startIndex>2</opensearch:startIndex>
+    <opensearch:itemsPerPage>2</opensearch:itemsPerPage>
+    <trackmatches>
+      <track>
+        <name>Believe</name>
+        <artist>Cher</artist>
+        <url>https://www.last.fm/music/Cher/_/Believe</url>
+        <streamable fulltrack="0">0</streamable>
+        <listeners>1182233</listeners>
+        <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/believe.png</image>
+        <image size="medium">https://lastfm.freetls.fastly.net/i/u/64s/believe.png</image>
+        <mbid>32ca187e-ee25-4f18-b7d0-3b6713f24635</mbid>
+      </track>
+      <track>
+        <name>Believer</name>
+        <artist>Imagine Dragons</artist>
+        <url>https://www.last.fm/music/Imagine+Dragons/_/Believer</url>
+        <streamable fulltrack="0">0</streamable>
+        <listeners>1015473</listeners>
+        <image size="small"></image>
+        <mbid></mbid>
+      </track>
+    </trackmatches>
+  </results>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let results = x.into_result().unwrap();
+        assert_eq!(results.search, "Believe");
+        assert_eq!(results.query.start_page, Some(2));
+        assert_eq!(
+            (
+                results.total_results,
+                results.start_index,
+                results.items_per_page
+            ),
+            (193_591, 2, 2)
+        );
+        assert!(results.has_more());
+        let tracks: Vec<_> = results
+            .matches
+            .tracks
+            .iter()
+            .map(|track| (track.name.as_str(), track.artist.as_str(), track.listeners))
+            .collect();
+        assert_eq!(
+            tracks,
+            [
+                ("Believe", "Cher", Some(1_182_233)),
+                ("Believer", "Imagine Dragons", Some(1_015_473))
+            ]
+        );
+        assert_eq!(results.matches.tracks[0].images.len(), 2);
+    }
+
+    const ARTIST_SEARCH: &str = r#"<results for="cher" xmlns:opensearch="http://a9.com/-/spec/opensearch/1.1/">
+  <opensearch:Query role="request" searchTerms="cher" startPage="1" />
+  <opensearch:totalResults>1231</opensearch:totalResults>
+  <opensearch:startIndex>0</opensearch:startIndex>
+  <opensearch:itemsPerPage>2</opensearch:itemsPerPage>
+  <artistmatches>
+    <artist>
+      <name>Cher</name>
+      <listeners>1545869</listeners>
+      <mbid>bfcc6d75-a6a5-4bc6-8282-47aec8531818</mbid>
+      <url>https://www.last.fm/music/Cher</url>
+      <streamable>0</streamable>
+      <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/cher.png</image>
+      <image size="mega"></image>
+    </artist>
+    <artist>
+      <name>Cheryl Cole</name>
+      <listeners>511924</listeners>
+      <mbid></mbid>
+      <url>https://www.last.fm/music/Cheryl+Cole</url>
+      <streamable>0</streamable>
+    </artist>
+  </artistmatches>
+</results>"#;
+
+    #[test]
+    fn album_search() {
+        let x: SearchAlbumsResponse = from_str(
+            r#"<lfm status="ok">
+<results for="believe" xmlns:opensearch="http://a9.com/-/spec/opensearch/1.1/">
+  <opensearch:Query role="request" searchTerms="believe" startPage="1" />
+  <opensearch:totalResults>63521</opensearch:totalResults>
+  <opensearch:startIndex>0</opensearch:startIndex>
+  <opensearch:itemsPerPage>2</opensearch:itemsPerPage>
+  <albummatches>
+    <album>
+      <name>Believe</name>
+      <artist>Cher</artist>
+      <url>https://www.last.fm/music/Cher/Believe</url>
+      <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/believe.png</image>
+      <image size="extralarge">https://lastfm.freetls.fastly.net/i/u/300x300/believe.png</image>
+      <streamable>0</streamable>
+      <mbid>63b3a8ca-26f2-4e2b-b867-647a6ec2bebd</mbid>
+    </album>
+    <album>
+      <name>Believe</name>
+      <artist>Justin Bieber</artist>
+      <url>https://www.last.fm/music/Justin+Bieber/Believe</url>
+      <image size="small"></image>
+      <streamable>0</streamable>
+      <mbid></mbid>
+    </album>
+  </albummatches>
+</results>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let results = x.into_result().unwrap();
+        assert_eq!(results.total_results, 63_521);
+        let albums: Vec<_> = results
+            .matches
+            .albums
+            .iter()
+            .map(|album| (album.artist.as_str(), album.mbid.as_deref()))
+            .collect();
+        assert_eq!(
+            albums,
+            [
+                ("Cher", Some("63b3a8ca-26f2-4e2b-b867-647a6ec2bebd")),
+                ("Justin Bieber", None)
+            ]
+        );
+        assert_eq!(results.matches.albums[0].images.len(), 2);
+    }
+
+    #[test]
+    fn artist_search() {
+        let results: ArtistSearchResults = from_str(ARTIST_SEARCH).expect("can parse");
+        assert_eq!(results.search, "cher");
+        assert_eq!(results.query.search_terms, "cher");
+        assert_eq!(results.total_results, 1231);
+        assert!(results.has_more());
+        let artists: Vec<_> = results
+            .matches
+            .artists
+            .iter()
+            .map(|artist| (artist.name.as_str(), artist.listeners))
+            .collect();
+        assert_eq!(
+            artists,
+            [("Cher", Some(1_545_869)), ("Cheryl Cole", Some(511_924))]
+        );
+        assert_eq!(results.matches.artists[0].images.len(), 2);
+
+        assert_round_trip::<ArtistSearchResults>(ARTIST_SEARCH);
+    }
+
+    #[test]
+    fn track_get_info() {
+        let x: GetTrackInfoResponse = from_str(
+            r#"<lfm status="ok">
+<track>
+  <name>Believe</name>
+  <mbid>32ca187e-ee25-4f18-b7d0-3b6713f24635</mbid>
+  <url>https://www.last.fm/music/Cher/_/Believe</url>
+  <duration>240000</duration>
+  <streamable fulltrack="0">0</streamable>
+  <listeners>1182233</listeners>
+  <playcount>7936312</playcount>
+  <artist>
+    <name>Cher</name>
+    <mbid>bfcc6d75-a6a5-4bc6-8282-47aec8531818</mbid>
+    <url>https://www.last.fm/music/Cher</url>
+  </artist>
+  <album position="1">
+    <artist>Cher</artist>
+    <title>Believe</title>
+    <mbid>63b3a8ca-26f2-4e2b-b867-647a6ec2bebd</mbid>
+    <url>https://www.last.fm/music/Cher/Believe</url>
+    <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/believe.png</image>
+  </album>
+  <userplaycount>12</userplaycount>
+  <userloved>1</userloved>
+  <toptags>
+    <tag>
+      <name>pop</name>
+      <url>https://www.last.fm/tag/pop</url>
+    </tag>
+  </toptags>
+</track>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let track = x.into_result().unwrap();
+        assert_eq!(track.duration, Some(240_000));
+        assert_eq!(track.listeners, 1_182_233);
+        assert_eq!(track.artist.name, "Cher");
+        let album = track.album.unwrap();
+        assert_eq!((album.position, album.name.as_str()), (Some(1), "Believe"));
+        assert_eq!(album.images.len(), 1);
+        assert_eq!(track.userloved, Some(true));
+        assert_eq!(track.tags.tags[0].name, "pop");
+
+        let x: GetTrackInfoResponse = from_str(
+            r#"<lfm status="ok">
+<track>
+  <name>Untitled</name>
+  <url>https://www.last.fm/music/Cher/_/Untitled</url>
+  <duration></duration>
+  <artist>
+    <name>Cher</name>
+    <url>https://www.last.fm/music/Cher</url>
+  </artist>
+  <toptags></toptags>
+</track>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let track = x.into_result().unwrap();
+        assert_eq!(track.duration, None);
+        assert_eq!(track.album, None);
+    }
+
+    #[test]
+    fn artist_get_info() {
+        let x: GetArtistInfoResponse = from_str(
+            r#"<lfm status="ok">
+<artist>
+  <name>Cher</name>
+  <mbid>bfcc6d75-a6a5-4bc6-8282-47aec8531818</mbid>
+  <url>https://www.last.fm/music/Cher</url>
+  <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/cher.png</image>
+  <image size="mega"></image>
+  <streamable>0</streamable>
+  <ontour>1</ontour>
+  <stats>
+    <listeners>1965286</listeners>
+    <playcount>52369813</playcount>
+    <userplaycount>12</userplaycount>
+  </stats>
+  <similar>
+    <artist>
+      <name>Madonna</name>
+      <url>https://www.last.fm/music/Madonna</url>
+      <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/madonna.png</image>
+    </artist>
+    <artist>
+      <name>Kylie Minogue</name>
+      <url>https://www.last.fm/music/Kylie+Minogue</url>
+      <image size="small"></image>
+    </artist>
+  </similar>
+  <tags>
+    <tag>
+      <name>pop</name>
+      <url>https://www.last.fm/tag/pop</url>
+    </tag>
+    <tag>
+      <name>female vocalists</name>
+      <url>https://www.last.fm/tag/female+vocalists</url>
+    </tag>
+  </tags>
+  <bio>
+    <links>
+      <link rel="original" href="https://last.fm/music/Cher/+wiki"></link>
+    </links>
+    <published>10 Jan 2006, 21:29</published>
+    <summary>Cher is an American singer and actress.</summary>
+    <content>Cher is an American singer and actress, known as the Goddess of Pop.</content>
+  </bio>
+</artist>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let LfmStatus::Ok(artist) = x else {
+            panic!("expected an artist, got {x:?}");
+        };
+        assert_eq!(artist.name, "Cher");
+        assert!(artist.ontour);
+        assert_eq!(
+            artist.stats,
+            ArtistStats {
+                listeners: 1965286,
+                playcount: 52369813,
+                userplaycount: Some(12),
+            }
+        );
+        assert_eq!(
+            artist.similar.artists[0],
+            ShortArtist {
+                name: "Madonna".into(),
+                mbid: String::new(),
+                url: "https://www.last.fm/music/Madonna".parse().unwrap(),
+                images: vec![i(
+                    ImageSize::Small,
+                    "https://lastfm.freetls.fastly.net/i/u/34s/madonna.png"
+                )],
+            }
+        );
+        assert_eq!(artist.similar.artists[1].name, "Kylie Minogue");
+        let tags: Vec<_> = artist
+            .tags
+            .tags
+            .iter()
+            .map(|tag| tag.name.as_str())
+            .collect();
+        assert_eq!(tags, ["pop", "female vocalists"]);
+        assert_eq!(artist.bio.published, "10 Jan 2006, 21:29");
+        assert_eq!(
+            artist.bio.links.links[0].href.as_str(),
+            "https://last.fm/music/Cher/+wiki"
+        );
+        assert!(artist.bio.content.starts_with("Cher is an American singer"));
+    }
+
+    #[test]
+    fn album_get_info() {
+        let x: GetAlbumInfoResponse = from_str(
+            r#"<lfm status="ok">
+<album>
+  <name>Believe</name>
+  <artist>Cher</artist>
+  <mbid>63b3a8ca-26f2-4e2b-b867-647a6ec2bebd</mbid>
+  <url>https://www.last.fm/music/Cher/Believe</url>
+  <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/believe.png</image>
+  <image size="mega"></image>
+  <listeners>405620</listeners>
+  <playcount>2853062</playcount>
+  <tracks>
+    <track rank="1">
+      <name>Believe</name>
+      <url>https://www.last.fm/music/Cher/_/Believe</url>
+      <duration>239</duration>
+      <streamable fulltrack="0">0</streamable>
+      <artist>
+        <name>Cher</name>
+        <mbid>bfcc6d75-a6a5-4bc6-8282-47aec8531818</mbid>
+        <url>https://www.last.fm/music/Cher</url>
+      </artist>
+    </track>
+    <track rank="2">
+      <name>The Power</name>
+      <url>https://www.last.fm/music/Cher/_/The+Power</url>
+      <duration></duration>
+      <streamable fulltrack="0">0</streamable>
+      <artist>
+        <name>Cher</name>
+        <mbid>bfcc6d75-a6a5-4bc6-8282-47aec8531818</mbid>
+        <url>https://www.last.fm/music/Cher</url>
+      </artist>
+    </track>
+  </tracks>
+  <tags>
+    <tag>
+      <name>pop</name>
+      <url>https://www.last.fm/tag/pop</url>
+    </tag>
+  </tags>
+  <wiki>
+    <published>27 Jul 2008, 15:55</published>
+    <summary>Believe is the twenty-third studio album by Cher.</summary>
+    <content>Believe is the twenty-third studio album by Cher, released in 1998.</content>
+  </wiki>
+</album>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let LfmStatus::Ok(album) = x else {
+            panic!("expected an album, got {x:?}");
+        };
+        assert_eq!(album.artist, "Cher");
+        assert_eq!(album.releasedate, None);
+        assert_eq!(album.release_year(), Some(2008));
+        let tracks: Vec<_> = album
+            .tracks
+            .tracks
+            .iter()
+            .map(|track| (track.rank, track.name.as_str(), track.duration))
+            .collect();
+        assert_eq!(
+            tracks,
+            [
+                (Some(1), "Believe", Some(239)),
+                (Some(2), "The Power", None)
+            ]
+        );
+        assert!(
+            album
+                .tracks
+                .tracks
+                .iter()
+                .all(|track| track.artist.name == "Cher")
+        );
+        assert_eq!(album.tags.tags[0].name, "pop");
+    }
+
+    #[test]
+    fn album_release_year() {
+        let x: GetAlbumInfoResponse = from_str(
+            r#"<lfm status="ok">
+<album>
+  <name>Believe</name>
+  <artist>Cher</artist>
+  <url>https://www.last.fm/music/Cher/Believe</url>
+  <releasedate>    6 Apr 1999, 00:00</releasedate>
+  <wiki>
+    <published>27 Jul 2008, 15:55</published>
+    <summary></summary>
+    <content></content>
+  </wiki>
+</album>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let mut album = x.into_result().unwrap();
+        assert_eq!(album.release_year(), Some(1999));
+
+        album.releasedate = None;
+        album.wiki = None;
+        assert_eq!(album.release_year(), None);
+    }
+
+    #[test]
+    fn chart_get_top_tags() {
+        let x: chart::GetTopTagsResponse = from_str(
+            r#"<lfm status="ok">
+<tags page="1" perPage="2" totalPages="1420" total="2839">
+  <tag>
+    <name>rock</name>
+    <url>https://www.last.fm/tag/rock</url>
+    <reach>401953</reach>
+    <taggings>4065223</taggings>
+    <streamable>1</streamable>
+    <wiki>
+      <published>Wed, 14 Nov 2007 00:55:52 +0000</published>
+      <summary>Rock music is a form of popular music.</summary>
+      <content>Rock music is a form of popular music with a prominent vocal melody.</content>
+    </wiki>
+  </tag>
+  <tag>
+    <name>electronic</name>
+    <url>https://www.last.fm/tag/electronic</url>
+    <reach>263854</reach>
+    <taggings>2440432</taggings>
+    <streamable>1</streamable>
+    <wiki></wiki>
+  </tag>
+</tags>
+</lfm>"#,
+        )
+        .expect("can parse");
+        assert_eq!(
             x,
-            TopAlbums {
-                user: "RJ".into(),
-                albums: vec![Album {
-                    rank: 1,
-                    name: "Images and Words".into(),
-                    playcount: 174,
-                    mbid: "f20971f2-c8ad-4d26-91ab-730f6dedafb2".into(),
-                    url: "http://www.last.fm/music/Dream+Theater/Images+and+Words"
+            LfmStatus::Ok(chart::TopTags {
+                page: 1,
+                per_page: 2,
+                total_pages: 1420,
+                total: 2839,
+                tags: vec![
+                    chart::Tag {
+                        name: "rock".into(),
+                        url: "https://www.last.fm/tag/rock".parse().unwrap(),
+                        reach: 401953,
+                        taggings: 4065223,
+                        streamable: true,
+                        wiki: chart::Wiki {
+                            published: "Wed, 14 Nov 2007 00:55:52 +0000".into(),
+                            summary: "Rock music is a form of popular music.".into(),
+                            content:
+                                "Rock music is a form of popular music with a prominent vocal melody."
+                                    .into(),
+                        },
+                    },
+                    chart::Tag {
+                        name: "electronic".into(),
+                        url: "https://www.last.fm/tag/electronic".parse().unwrap(),
+                        reach: 263854,
+                        taggings: 2440432,
+                        streamable: true,
+                        wiki: chart::Wiki::default(),
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn chart_get_top_tags_without_paging() {
+        for empty in [
+            r#"<lfm status="ok"><tags page="1" perPage="50" totalPages="0" total="0"></tags></lfm>"#,
+            r#"<lfm status="ok"><tags/></lfm>"#,
+        ] {
+            let x: chart::GetTopTagsResponse = from_str(empty).expect("can parse");
+            let top = x.into_result().unwrap();
+            assert_eq!((top.total, top.tags.len()), (0, 0));
+        }
+    }
+
+    #[test]
+    fn chart_get_top_tracks() {
+        let x: chart::GetTopTracksResponse = from_str(
+            r#"<lfm status="ok">
+<tracks page="1" perPage="1" totalPages="4203" total="4203">
+  <track>
+    <name>Blinding Lights</name>
+    <duration>200</duration>
+    <playcount>41267023</playcount>
+    <listeners>2210874</listeners>
+    <mbid></mbid>
+    <url>https://www.last.fm/music/The+Weeknd/_/Blinding+Lights</url>
+    <streamable fulltrack="0">0</streamable>
+    <artist>
+      <name>The Weeknd</name>
+      <mbid>c8b03190-306c-4120-bb0b-6f2ebfc06ea9</mbid>
+      <url>https://www.last.fm/music/The+Weeknd</url>
+    </artist>
+    <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/2a96cbd8b46e442fc41c2b86b821562f.png</image>
+  </track>
+</tracks>
+</lfm>"#,
+        )
+        .expect("can parse");
+        assert_eq!(
+            x,
+            LfmStatus::Ok(chart::TopTracks {
+                page: 1,
+                per_page: 1,
+                total_pages: 4203,
+                total: 4203,
+                tracks: vec![chart::Track {
+                    name: "Blinding Lights".into(),
+                    duration: 200,
+                    playcount: 41267023,
+                    listeners: 2210874,
+                    mbid: String::new(),
+                    url: "https://www.last.fm/music/The+Weeknd/_/Blinding+Lights"
                         .parse()
                         .unwrap(),
+                    streamable: false,
                     artist: ShortArtist {
-                        name: "Dream Theater".into(),
-                        mbid: "28503ab7-8bf2-4666-a7bd-2644bfc7cb1d".into(),
-                        url: "http://www.last.fm/music/Dream+Theater".parse().unwrap()
+                        name: "The Weeknd".into(),
+                        mbid: "c8b03190-306c-4120-bb0b-6f2ebfc06ea9".into(),
+                        url: "https://www.last.fm/music/The+Weeknd".parse().unwrap(),
+                        images: Vec::new(),
                     },
-                    images: vec![
-                        i(ImageSize::Small, "..."),
-                        i(ImageSize::Medium, "..."),
-                        i(ImageSize::Large, "..."),
-                    ]
-                }]
-            }
+                    images: vec![i(
+                        ImageSize::Small,
+                        "https://lastfm.freetls.fastly.net/i/u/34s/2a96cbd8b46e442fc41c2b86b821562f.png"
+                    )],
+                }],
+            })
         );
     }
 
     #[test]
-    fn user_get_top_artists() {
-        let x: TopArtists = from_str(
-            r#"<topartists user="RJ" type="overall">
-  <artist rank="1">
-    <name>Dream Theater</name>
-    <playcount>1337</playcount>
-    <mbid>28503ab7-8bf2-4666-a7bd-2644bfc7cb1d</mbid>
-    <url>http://www.last.fm/music/Dream+Theater</url>
-    <streamable>1</streamable>
-    <image size="small">...</image>
-    <image size="medium">...</image>
-    <image size="large">...</image>
+    fn chart_get_top_artists() {
+        let x: chart::GetTopArtistsResponse = from_str(
+            r#"<lfm status="ok">
+<artists page="1" perPage="2" totalPages="1250" total="2500">
+  <artist>
+    <name>The Weeknd</name>
+    <playcount>263618340</playcount>
+    <listeners>4409853</listeners>
+    <mbid>c8b03190-306c-4120-bb0b-6f2ebfc06ea9</mbid>
+    <url>https://www.last.fm/music/The+Weeknd</url>
+    <streamable>0</streamable>
+    <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/2a96cbd8b46e442fc41c2b86b821562f.png</image>
+    <image size="medium">https://lastfm.freetls.fastly.net/i/u/64s/2a96cbd8b46e442fc41c2b86b821562f.png</image>
+  </artist>
+  <artist>
+    <name>Kanye West</name>
+    <playcount>345453812</playcount>
+    <listeners>6124376</listeners>
+    <mbid>164f0d73-1234-4e2c-8743-d77bf2191051</mbid>
+    <url>https://www.last.fm/music/Kanye+West</url>
+    <streamable>0</streamable>
+    <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/2a96cbd8b46e442fc41c2b86b821562f.png</image>
   </artist>
-</topartists>"#,
+</artists>
+</lfm>"#,
         )
         .expect("can parse");
+        let LfmStatus::Ok(top) = x else {
+            panic!("expected ok status, got {x:?}");
+        };
         assert_eq!(
-            x,
-            TopArtists {
-                user: "RJ".into(),
-                artists: vec![Artist {
-                    rank: 1,
-                    name: "Dream Theater".into(),
-                    playcount: 1337,
-                    mbid: "28503ab7-8bf2-4666-a7bd-2644bfc7cb1d".into(),
-                    url: "http://www.last.fm/music/Dream+Theater".parse().unwrap(),
-                    streamable: true,
-                    images: vec![
-                        i(ImageSize::Small, "..."),
-                        i(ImageSize::Medium, "..."),
-                        i(ImageSize::Large, "..."),
-                    ]
-                }]
+            (top.page, top.per_page, top.total_pages, top.total),
+            (1, 2, 1250, 2500)
+        );
+        assert_eq!(
+            top.artists[0],
+            chart::Artist {
+                name: "The Weeknd".into(),
+                playcount: 263618340,
+                listeners: 4409853,
+                mbid: "c8b03190-306c-4120-bb0b-6f2ebfc06ea9".into(),
+                url: "https://www.last.fm/music/The+Weeknd".parse().unwrap(),
+                streamable: false,
+                images: vec![
+                    i(
+                        ImageSize::Small,
+                        "https://lastfm.freetls.fastly.net/i/u/34s/2a96cbd8b46e442fc41c2b86b821562f.png"
+                    ),
+                    i(
+                        ImageSize::Medium,
+                        "https://lastfm.freetls.fastly.net/i/u/64s/2a96cbd8b46e442fc41c2b86b821562f.png"
+                    ),
+                ],
             }
+        );
+        assert_eq!(top.artists[1].name, "Kanye West");
+        assert_round_trip::<chart::TopArtists>(&quick_xml::se::to_string(&top).unwrap());
+    }
+
+    #[test]
+    fn geo_get_top_artists() {
+        let x: geo::GetTopArtistsResponse = from_str(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<lfm status="ok">
+  <topartists country="Germany" page="1" perPage="2" totalPages="587331" total="1174662">
+    <artist rank="1">
+      <name>Rammstein</name>
+      <listeners>1394513</listeners>
+      <mbid>b2d122f9-eadb-4930-a196-8f221eeb0c66</mbid>
+      <url>https://www.last.fm/music/Rammstein</url>
+      <streamable>0</streamable>
+      <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/rammstein.png</image>
+    </artist>
+    <artist rank="2">
+      <name>Linkin Park</name>
+      <playcount>721301566</playcount>
+      <listeners>1256077</listeners>
+      <mbid></mbid>
+      <url>https://www.last.fm/music/Linkin+Park</url>
+      <streamable>0</streamable>
+    </artist>
+  </topartists>
+</lfm>"#,
         )
+        .expect("can parse");
+        let top = x.into_result().unwrap();
+        assert_eq!(top.country, "Germany");
+        assert_eq!(
+            (top.page, top.per_page, top.total_pages, top.total),
+            (1, 2, 587331, 1174662)
+        );
+        assert_eq!(
+            top.artists[0],
+            geo::Artist {
+                rank: Some(1),
+                name: "Rammstein".into(),
+                playcount: None,
+                listeners: 1394513,
+                mbid: "b2d122f9-eadb-4930-a196-8f221eeb0c66".into(),
+                url: "https://www.last.fm/music/Rammstein".parse().unwrap(),
+                streamable: false,
+                images: vec![i(
+                    ImageSize::Small,
+                    "https://lastfm.freetls.fastly.net/i/u/34s/rammstein.png"
+                )],
+            }
+        );
+        assert_eq!(
+            (top.artists[1].playcount, top.artists[1].listeners),
+            (Some(721301566), 1256077)
+        );
     }
 
     #[test]
-    fn display_period() {
-        assert_eq!(Period::Overall.to_string(), "overall");
+    fn geo_get_top_tracks() {
+        let x: geo::GetTopTracksResponse = from_str(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<lfm status="ok">
+  <tracks country="Germany" page="1" perPage="2" totalPages="5000" total="10000">
+    <track rank="0">
+      <name>Blinding Lights</name>
+      <duration>200</duration>
+      <listeners>3612841</listeners>
+      <mbid>5c7b0a4f-2d64-4c0c-8b1f-8f1b8e9a3c11</mbid>
+      <url>https://www.last.fm/music/The+Weeknd/_/Blinding+Lights</url>
+      <streamable fulltrack="0">0</streamable>
+      <artist>
+        <name>The Weeknd</name>
+        <mbid>c8b03190-306c-4120-bb0b-6f2ebfc06ea9</mbid>
+        <url>https://www.last.fm/music/The+Weeknd</url>
+      </artist>
+      <image size="small">https://lastfm.freetls.fastly.net/i/u/34s/blinding.png</image>
+    </track>
+    <track rank="1">
+      <name>Du hast</name>
+      <duration>0</duration>
+      <listeners>1148710</listeners>
+      <mbid></mbid>
+      <url>https://www.last.fm/music/Rammstein/_/Du+hast</url>
+      <streamable fulltrack="0">0</streamable>
+      <artist>
+        <name>Rammstein</name>
+        <mbid>b2d122f9-eadb-4930-a196-8f221eeb0c66</mbid>
+        <url>https://www.last.fm/music/Rammstein</url>
+      </artist>
+    </track>
+  </tracks>
+</lfm>"#,
+        )
+        .expect("can parse");
+        let top = x.into_result().unwrap();
+        assert_eq!(top.country, "Germany");
+        assert_eq!((top.page, top.total), (1, 10000));
+        assert_eq!(
+            top.tracks[0],
+            geo::Track {
+                rank: Some(0),
+                name: "Blinding Lights".into(),
+                duration: 200,
+                listeners: 3612841,
+                mbid: Some("5c7b0a4f-2d64-4c0c-8b1f-8f1b8e9a3c11".into()),
+                url: "https://www.last.fm/music/The+Weeknd/_/Blinding+Lights"
+                    .parse()
+                    .unwrap(),
+                streamable: false,
+                artist: ShortArtist {
+                    name: "The Weeknd".into(),
+                    mbid: "c8b03190-306c-4120-bb0b-6f2ebfc06ea9".into(),
+                    url: "https://www.last.fm/music/The+Weeknd".parse().unwrap(),
+                    images: Vec::new(),
+                },
+                images: vec![i(
+                    ImageSize::Small,
+                    "https://lastfm.freetls.fastly.net/i/u/34s/blinding.png"
+                )],
+            }
+        );
+        let second = &top.tracks[1];
+        assert_eq!((second.mbid.as_deref(), second.duration), (None, 0));
+        assert_eq!(second.artist.name, "Rammstein");
     }
 }
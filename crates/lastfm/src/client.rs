@@ -0,0 +1,2204 @@
+//! The HTTP client, available with the `client` feature.
+
+use std::{
+    any::Any,
+    borrow::Cow,
+    collections::HashMap,
+    fmt,
+    marker::PhantomData,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use futures_util::{Stream, stream};
+use md5::{Digest, Md5};
+use reqwest::{
+    StatusCode,
+    header::{ACCEPT, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+};
+use serde::{
+    Deserialize,
+    de::{DeserializeOwned, IgnoredAny},
+};
+use url::Url;
+
+use crate::{
+    Error,
+    api_types::{
+        self, AlbumInfo, AlbumSearchResults, ArtistCorrection, ArtistInfo, ArtistSearchResults,
+        Friends, LovedTracks, PersonalTagAlbums, RecentTrack, RecentTracks, ShortArtist,
+        SimilarArtists, SimilarTracks, TagInfo, TagTopAlbums, TagTopArtists, TopAlbums, TopArtists,
+        TopTracks, TrackInfo, TrackSearchResults, UserSummary, WeeklyAlbum, WeeklyAlbumChart,
+        WeeklyChartList,
+    },
+    cache::TtlCache,
+    decode, decode_json, methods,
+    retry::Backoff,
+};
+
+macro_rules! request_builder {
+    (
+        $(#[doc = $doc:literal])?
+        struct $name:ident<$life:lifetime> {
+        method: $method:expr,
+        required: { $(
+            $required:ident: $rtyp:ty
+        ),* $(,)? } $(,)?
+        optional: { $(
+            $( #[doc = $odoc:literal] )?
+            $optional:ident: $otyp:ty
+            ),* $(,)? } $(,)?
+        }
+        => $api:ty
+        => $ret:ty ) => {
+        $(#[doc = $doc])?
+        pub struct $name<$life> {
+            client: &$life Client,
+            credentials: Option<Credentials<$life>>,
+            $($required: $rtyp,)*
+            $($optional: Option<$otyp>,)*
+        }
+
+        impl<$life> $name<$life> {
+            fn new(client: &$life Client, $($required: impl Into<$rtyp>),*) -> Self {
+                Self {
+                    client,
+                    credentials: None,
+                    $($required: $required.into(),)*
+                    $($optional: None),*
+                }
+            }
+
+            /// Sign this request with another api key and shared secret than the client's, e.g.
+            /// to serve several deployments with their own keys from one client.
+            pub fn credentials(mut self, api_key: &$life str, shared_secret: &$life str) -> Self {
+                self.credentials = Some(Credentials {
+                    api_key,
+                    shared_secret,
+                });
+                self
+            }
+
+            $(
+                $( #[doc = $odoc] )?
+                pub fn $optional(mut self, $optional: $otyp ) -> Self {
+                    self.$optional = Some($optional);
+                    self
+                }
+            )*
+
+            /// Compute the arguments of this request, to look at or send them (again) later.
+            pub fn prepare(self) -> PreparedRequest<$life, $api> {
+                #[allow(unused_mut)]
+                let mut args: Vec<(&'static str, String)> = vec![
+                    $((stringify!($required), self.$required.to_string())),*
+                ];
+
+                $(
+                    if let Some($optional) = self.$optional {
+                        args.push((stringify!($optional), $optional.to_string()));
+                    }
+                )*
+
+                PreparedRequest {
+                    client: self.client,
+                    method: $method,
+                    args,
+                    credentials: self.credentials,
+                    response: PhantomData,
+                }
+            }
+
+            pub async fn send(self) -> Result<$ret, Error> {
+                self.prepare().send().await
+            }
+
+        }
+    };
+}
+
+/// The api key and shared secret a request is signed with.
+#[derive(Debug, Clone, Copy)]
+struct Credentials<'a> {
+    api_key: &'a str,
+    shared_secret: &'a str,
+}
+
+/// A request whose arguments are computed, returned by the `prepare` method of the request
+/// builders. It can be sent any number of times, e.g. to retry it.
+pub struct PreparedRequest<'a, T> {
+    client: &'a Client,
+    method: &'static str,
+    args: Vec<(&'static str, String)>,
+    credentials: Option<Credentials<'a>>,
+    response: PhantomData<fn() -> T>,
+}
+
+impl<T> PreparedRequest<'_, T> {
+    pub fn method(&self) -> &str {
+        self.method
+    }
+
+    /// The arguments of the method, without the `method`, `api_key` and `api_sig` added when
+    /// it is sent.
+    pub fn args(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.args.iter().map(|(key, value)| (*key, value.as_str()))
+    }
+
+    /// The URL this request is sent to, signed.
+    pub fn signed_url(&self) -> Result<Url, Error> {
+        let request = self
+            .client
+            .build_request(self.method, self.args(), self.credentials)?;
+        Ok(request.url().clone())
+    }
+}
+
+impl<T> PreparedRequest<'_, api_types::LfmStatus<T>>
+where
+    T: DeserializeOwned + Clone + Send + Sync + 'static,
+{
+    pub async fn send(&self) -> Result<T, Error> {
+        self.client
+            .make_request::<api_types::LfmStatus<T>>(self.method, self.args(), self.credentials)
+            .await?
+            .into_result()
+            .map_err(Error::Api)
+    }
+}
+
+/// A response Last.fm sent validators for, so it can be requested conditionally.
+#[derive(Clone)]
+struct Conditional {
+    etag: Option<reqwest::header::HeaderValue>,
+    last_modified: Option<reqwest::header::HeaderValue>,
+    /// The parsed response, handed out again when Last.fm answers `304 Not Modified`.
+    value: Arc<dyn Any + Send + Sync>,
+}
+
+impl fmt::Debug for Conditional {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Conditional")
+            .field("etag", &self.etag)
+            .field("last_modified", &self.last_modified)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Sort `args` into the order Last.fm expects and compute their `api_sig`.
+///
+/// The signature covers the raw values; they are only URL-encoded afterwards when the query is
+/// built, so it must not be computed from the encoded form.
+pub fn sign(args: &mut [(&str, &str)], shared_secret: &str) -> String {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    // sorting whole pairs keeps repeated keys in a fixed order too
+    args.sort_unstable();
+    let mut hasher = Md5::new();
+    for (k, v) in args.iter() {
+        hasher.update(k);
+        hasher.update(v);
+    }
+    hasher.update(shared_secret);
+    let digest = hasher.finalize();
+    let mut signature = String::with_capacity(2 * digest.len());
+    for byte in digest {
+        signature.push(HEX[usize::from(byte >> 4)].into());
+        signature.push(HEX[usize::from(byte & 0xf)].into());
+    }
+    signature
+}
+
+/// The largest page size Last.fm accepts, used when walking through every page.
+const PAGE_SIZE: usize = 1000;
+
+/// A Last.fm API client.
+///
+/// It spawns no background tasks of its own: the caches are swept as they are written to, and
+/// the pooled connections of the underlying HTTP client close once it is dropped.
+pub struct Client {
+    shared_secret: String,
+    api_key: String,
+    client: reqwest::Client,
+    base_url: String,
+    backoff: Backoff,
+    max_retries: u32,
+    slow_request_threshold: Duration,
+    timeout: Option<Duration>,
+    /// Overrides of `timeout` by method name.
+    method_timeouts: HashMap<String, Duration>,
+    format: Format,
+    album_counts: TtlCache<String, usize>,
+    /// Responses by request URL. Only those carrying an `ETag` or `Last-Modified` are kept, as
+    /// the others cannot be revalidated.
+    conditionals: TtlCache<String, Conditional>,
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("shared_secret", &"<shared_secret>")
+            .field("api_key", &"<api_key>")
+            .field("client", &self.client)
+            .field("base_url", &self.base_url)
+            .field("backoff", &self.backoff)
+            .field("max_retries", &self.max_retries)
+            .field("slow_request_threshold", &self.slow_request_threshold)
+            .field("timeout", &self.timeout)
+            .field("method_timeouts", &self.method_timeouts)
+            .field("format", &self.format)
+            .field("album_counts", &self.album_counts)
+            .field("conditionals", &self.conditionals)
+            .finish()
+    }
+}
+
+/// The format Last.fm is asked to answer in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Xml,
+    /// Sent with `format=json` and read with [`decode_json`].
+    Json,
+}
+
+pub struct ClientBuilder {
+    shared_secret: String,
+    api_key: String,
+    base_url: String,
+    backoff: Backoff,
+    max_retries: u32,
+    slow_request_threshold: Duration,
+    timeout: Option<Duration>,
+    method_timeouts: HashMap<String, Duration>,
+    format: Format,
+    http2_prior_knowledge: bool,
+    pool_idle_timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
+}
+
+impl ClientBuilder {
+    /// The API root to send requests to. Defaults to `https://ws.audioscrobbler.com/2.0/`.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// The backoff schedule used between retries of transient failures.
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// How many times a request failing with a temporary error (code 16) or for exceeding the
+    /// rate limit (code 29) is sent again, waiting as long as the [`backoff`](Self::backoff)
+    /// says in between. Other errors are returned right away. Defaults to 0, never retrying.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Requests taking longer than this are logged as a warning. Defaults to 2 seconds.
+    pub fn slow_request_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_request_threshold = threshold;
+        self
+    }
+
+    /// How long a request may take, from connecting until the whole response is read. Unlimited
+    /// by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Use `timeout` instead of the one set with [`ClientBuilder::timeout`] for `method`, one of
+    /// the names in [`methods`].
+    pub fn method_timeout(mut self, method: &str, timeout: Duration) -> Self {
+        self.method_timeouts.insert(method.to_owned(), timeout);
+        self
+    }
+
+    /// The format responses are requested in. Defaults to [`Format::Xml`]; the response types are
+    /// the same either way.
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Talk HTTP/2 without negotiating it first. Only for servers known to support it, such as a
+    /// proxy in front of Last.fm; off by default.
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.http2_prior_knowledge = true;
+        self
+    }
+
+    /// How long an unused connection is kept open for the next request. Defaults to 30 seconds,
+    /// below the idle timeout of Last.fm's servers so that requests do not pick a connection
+    /// they are about to close. `None` keeps connections forever.
+    pub fn pool_idle_timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.pool_idle_timeout = timeout.into();
+        self
+    }
+
+    /// The interval of TCP keepalive probes on open connections. Defaults to 60 seconds; `None`
+    /// sends none.
+    pub fn tcp_keepalive(mut self, interval: impl Into<Option<Duration>>) -> Self {
+        self.tcp_keepalive = interval.into();
+        self
+    }
+
+    /// Fails when the shared secret or api key is empty, as every request would be rejected.
+    pub fn build(self) -> Result<Client, Error> {
+        if self.api_key.is_empty() {
+            return Err(Error::MissingCredential("api key"));
+        }
+        if self.shared_secret.is_empty() {
+            return Err(Error::MissingCredential("shared secret"));
+        }
+        let mut client = reqwest::Client::builder()
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive);
+        if self.http2_prior_knowledge {
+            client = client.http2_prior_knowledge();
+        }
+        Ok(Client {
+            shared_secret: self.shared_secret,
+            api_key: self.api_key,
+            client: client.build().map_err(Error::Http)?,
+            base_url: self.base_url,
+            backoff: self.backoff,
+            max_retries: self.max_retries,
+            slow_request_threshold: self.slow_request_threshold,
+            timeout: self.timeout,
+            method_timeouts: self.method_timeouts,
+            format: self.format,
+            album_counts: TtlCache::new(Duration::from_secs(5 * 60)),
+            conditionals: TtlCache::new(Duration::from_secs(60 * 60)),
+        })
+    }
+}
+
+impl Client {
+    pub fn new(shared_secret: String, api_key: String) -> Result<Self, Error> {
+        Self::builder(shared_secret, api_key).build()
+    }
+
+    pub fn builder(shared_secret: String, api_key: String) -> ClientBuilder {
+        ClientBuilder {
+            shared_secret,
+            api_key,
+            base_url: "https://ws.audioscrobbler.com/2.0/".into(),
+            backoff: Backoff::default(),
+            max_retries: 0,
+            slow_request_threshold: Duration::from_secs(2),
+            timeout: None,
+            method_timeouts: HashMap::new(),
+            format: Format::default(),
+            http2_prior_knowledge: false,
+            pool_idle_timeout: Some(Duration::from_secs(30)),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+        }
+    }
+
+    /// A request for `method` signed with `credentials`, or else the client's own, with the
+    /// timeout configured for it.
+    fn build_request<'a>(
+        &'a self,
+        method: &'a str,
+        args: impl IntoIterator<Item = (&'a str, &'a str)>,
+        credentials: Option<Credentials<'a>>,
+    ) -> Result<reqwest::Request, Error> {
+        let credentials = credentials.unwrap_or(Credentials {
+            api_key: &self.api_key,
+            shared_secret: &self.shared_secret,
+        });
+        let mut args: Vec<_> = args.into_iter().collect();
+        args.reserve_exact(2);
+        args.push(("method", method));
+        args.push(("api_key", credentials.api_key));
+        let signature = sign(&mut args, credentials.shared_secret);
+
+        let mut request = self
+            .client
+            .get(&self.base_url)
+            .query(&args)
+            .query(&[("api_sig", signature)]);
+        // Last.fm leaves the format out of the signature
+        request = match self.format {
+            Format::Xml => request.header(ACCEPT, "application/xml"),
+            Format::Json => request
+                .query(&[("format", "json")])
+                .header(ACCEPT, "application/json"),
+        };
+        let mut request = request.build().map_err(Error::Http)?;
+        *request.timeout_mut() = self.method_timeouts.get(method).copied().or(self.timeout);
+        Ok(request)
+    }
+
+    /// Whether `body` is a Last.fm error that may be gone when the request is sent again.
+    fn is_transient_failure(&self, body: &str) -> bool {
+        let status = match self.format {
+            Format::Xml => decode::<api_types::LfmStatus<IgnoredAny>>(body),
+            Format::Json => decode_json::<api_types::LfmStatus<IgnoredAny>>(body),
+        };
+        matches!(status, Ok(api_types::LfmStatus::Failed(e)) if e.code == "16" || e.code == "29")
+    }
+
+    fn warn_if_slow(&self, method: &str, started: Instant) {
+        let elapsed = started.elapsed();
+        if elapsed > self.slow_request_threshold {
+            tracing::warn!(method, ?elapsed, "slow Last.fm request");
+        }
+    }
+
+    async fn make_request<'a, T>(
+        &'a self,
+        method: &'a str,
+        args: impl IntoIterator<Item = (&'a str, &'a str)>,
+        credentials: Option<Credentials<'a>>,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned + Clone + Send + Sync + 'static,
+    {
+        use Error::Http;
+
+        let mut request = self.build_request(method, args, credentials)?;
+        let key = request.url().to_string();
+        // a value of another type could not be handed out for a `304`
+        let cached = self
+            .conditionals
+            .get(&key)
+            .filter(|cached| cached.value.is::<T>());
+        if let Some(cached) = &cached {
+            let headers = request.headers_mut();
+            if let Some(etag) = &cached.etag {
+                headers.insert(IF_NONE_MATCH, etag.clone());
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                headers.insert(IF_MODIFIED_SINCE, last_modified.clone());
+            }
+        }
+
+        let mut attempt = 0;
+        let (etag, last_modified, body) = loop {
+            let started = Instant::now();
+            let retry = request.try_clone().expect("GET requests have no body");
+            let resp = self.client.execute(retry).await.map_err(Http)?;
+            if resp.status() == StatusCode::NOT_MODIFIED
+                && let Some(value) = cached
+                    .as_ref()
+                    .and_then(|cached| cached.value.downcast_ref::<T>().cloned())
+            {
+                return Ok(value);
+            }
+
+            let etag = resp.headers().get(ETAG).cloned();
+            let last_modified = resp.headers().get(LAST_MODIFIED).cloned();
+            let body = resp.text().await.map_err(Http)?;
+            self.warn_if_slow(method, started);
+            if attempt < self.max_retries && self.is_transient_failure(&body) {
+                let delay = self.backoff.delay(attempt);
+                tracing::debug!(method, attempt, ?delay, "retrying Last.fm request");
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+                continue;
+            }
+            break (etag, last_modified, body);
+        };
+        let value: T = match self.format {
+            Format::Xml => decode(&body)?,
+            Format::Json => decode_json(&body)?,
+        };
+        if etag.is_some() || last_modified.is_some() {
+            let conditional = Conditional {
+                etag,
+                last_modified,
+                value: Arc::new(value.clone()),
+            };
+            self.conditionals.insert(key, conditional);
+        }
+        Ok(value)
+    }
+
+    /// Sign and send any method with `params`, returning the response body as is.
+    ///
+    /// An escape hatch for methods this crate does not model (yet): parse the body with
+    /// [`decode`], or [`decode_json`] for [`Format::Json`], and a type of your own. Responses are
+    /// not cached or retried, and Last.fm errors are only found in the body.
+    pub async fn raw_get(&self, method: &str, params: &[(&str, &str)]) -> Result<String, Error> {
+        let request = self.build_request(method, params.iter().copied(), None)?;
+        let started = Instant::now();
+        let resp = self.client.execute(request).await.map_err(Error::Http)?;
+        let body = resp.text().await.map_err(Error::Http)?;
+        self.warn_if_slow(method, started);
+        Ok(body)
+    }
+
+    pub async fn authenticate(&self, token: &str) -> Result<api_types::Session, Error> {
+        self.make_request::<api_types::AuthGetSessionResponse>(
+            methods::AUTH_GET_SESSION,
+            [("token", token)],
+            None,
+        )
+        .await?
+        .0
+        .into_result()
+        .map_err(Error::Api)
+    }
+
+    /// Check that `session_key` is still accepted, returning the name of its user.
+    ///
+    /// Last.fm has no call for this, so this fetches the profile of the session's user, which
+    /// requires a valid key. Revoked keys fail with [`Error::InvalidSession`].
+    pub async fn validate_session(&self, session_key: &str) -> Result<String, Error> {
+        #[derive(Deserialize, Clone)]
+        #[serde(rename = "user")]
+        struct SessionUser {
+            name: String,
+        }
+
+        let user = self
+            .make_request::<api_types::LfmStatus<SessionUser>>(
+                methods::USER_GET_INFO,
+                [("sk", session_key)],
+                None,
+            )
+            .await?
+            .into_result();
+        match user {
+            Ok(user) => Ok(user.name),
+            Err(e) if e.code == "9" => Err(Error::InvalidSession),
+            Err(e) => Err(Error::Api(e)),
+        }
+    }
+
+    pub fn top_tracks<'a>(&'a self, user: impl Into<Cow<'a, str>>) -> GetTopTracks<'a> {
+        GetTopTracks::new(self, user)
+    }
+
+    pub fn top_albums<'a>(&'a self, user: impl Into<Cow<'a, str>>) -> GetTopAlbums<'a> {
+        GetTopAlbums::new(self, user)
+    }
+
+    /// Every page of `user`'s top albums over `period`, fetched one at a time as the stream is
+    /// polled. Ends after the last page or at the first error.
+    ///
+    /// Last.fm answers a page past the end with either an empty list or an "invalid parameters"
+    /// error, depending on its mood. Both end the stream cleanly once at least one page was
+    /// fetched.
+    pub fn top_albums_pages<'a>(
+        &'a self,
+        user: &'a str,
+        period: api_types::Period,
+    ) -> impl Stream<Item = Result<TopAlbums, Error>> + 'a {
+        stream::try_unfold(Some(1), move |page| async move {
+            let Some(page) = page else {
+                return Ok(None);
+            };
+            let albums = self
+                .top_albums(user)
+                .period(period)
+                .page(page)
+                .limit(PAGE_SIZE)
+                .send()
+                .await;
+            let albums = match albums {
+                Err(Error::Api(e)) if page > 1 && e.code == "6" => return Ok(None),
+                Ok(albums) if page > 1 && albums.albums.is_empty() => return Ok(None),
+                albums => albums?,
+            };
+            let next = (page < albums.total_pages && !albums.albums.is_empty()).then_some(page + 1);
+            Ok(Some((albums, next)))
+        })
+    }
+
+    /// The albums `user` listened to most between the unix timestamps `from` and `to`, best
+    /// first.
+    ///
+    /// Unlike [`Client::top_albums`], which only knows the fixed [`api_types::Period`]s counting
+    /// back from now, this accepts any range. It is backed by `user.getWeeklyAlbumChart`, so
+    /// Last.fm rounds the range to the weekly chart boundaries it has computed for the user, and
+    /// the entries carry less detail than [`api_types::Album`] (e.g. no images).
+    pub async fn top_albums_between(
+        &self,
+        user: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<WeeklyAlbum>, Error> {
+        let mut albums = self
+            .weekly_album_chart(user)
+            .from(from)
+            .to(to)
+            .send()
+            .await?
+            .albums;
+        albums.sort_by_key(|album| album.rank);
+        Ok(albums)
+    }
+
+    /// The albums `user` listened to in one of the weekly charts Last.fm computed for them, by
+    /// default the latest one.
+    pub fn weekly_album_chart<'a>(
+        &'a self,
+        user: impl Into<Cow<'a, str>>,
+    ) -> GetWeeklyAlbumChart<'a> {
+        GetWeeklyAlbumChart::new(self, user)
+    }
+
+    /// The ranges of the weekly charts available for `user`, to pass to
+    /// [`Client::weekly_album_chart`].
+    pub fn weekly_chart_list<'a>(
+        &'a self,
+        user: impl Into<Cow<'a, str>>,
+    ) -> GetWeeklyChartList<'a> {
+        GetWeeklyChartList::new(self, user)
+    }
+
+    /// The number of distinct albums in `user`'s library.
+    ///
+    /// Only fetches a single album and reads the total from the pagination attributes, so this is
+    /// cheap. The result is cached for a few minutes.
+    pub async fn album_count(&self, user: &str) -> Result<usize, Error> {
+        if let Some(count) = self.album_counts.get(&user.to_owned()) {
+            return Ok(count);
+        }
+        let count = self.top_albums(user).limit(1).send().await?.total;
+        self.album_counts.insert(user.to_owned(), count);
+        Ok(count)
+    }
+
+    /// Drop everything cached about `user`, so that the next requests fetch fresh data.
+    pub fn forget_user(&self, user: &str) {
+        self.album_counts.evict(|cached| cached == user);
+        self.conditionals.evict(|request| {
+            Url::parse(request).is_ok_and(|url| {
+                url.query_pairs()
+                    .any(|(key, value)| key == "user" && value == user)
+            })
+        });
+    }
+
+    pub fn recent_tracks<'a>(&'a self, user: impl Into<Cow<'a, str>>) -> GetRecentTracks<'a> {
+        GetRecentTracks::new(self, user)
+    }
+
+    /// The track `user` is listening to right now, if any.
+    pub async fn now_playing(&self, user: &str) -> Result<Option<RecentTrack>, Error> {
+        let tracks = self.recent_tracks(user).limit(1).send().await?.tracks;
+        Ok(tracks.into_iter().next().filter(|track| track.now_playing))
+    }
+
+    /// The albums `user` tagged with `tag`.
+    pub fn personal_tag_albums<'a>(
+        &'a self,
+        user: impl Into<Cow<'a, str>>,
+        tag: impl Into<Cow<'a, str>>,
+    ) -> GetPersonalTagAlbums<'a> {
+        GetPersonalTagAlbums::new(self, user, tag, "album")
+    }
+
+    /// The tracks `user` loved, most recently loved first.
+    pub fn loved_tracks<'a>(&'a self, user: impl Into<Cow<'a, str>>) -> GetLovedTracks<'a> {
+        GetLovedTracks::new(self, user)
+    }
+
+    /// The profile of `user`, e.g. their scrobble count and when they signed up.
+    pub fn user_info<'a>(&'a self, user: impl Into<Cow<'a, str>>) -> GetUserInfo<'a> {
+        GetUserInfo::new(self, user)
+    }
+
+    pub fn friends<'a>(&'a self, user: impl Into<Cow<'a, str>>) -> GetFriends<'a> {
+        GetFriends::new(self, user)
+    }
+
+    pub fn top_artists<'a>(&'a self, user: impl Into<Cow<'a, str>>) -> GetTopArtists<'a> {
+        GetTopArtists::new(self, user)
+    }
+
+    /// The albums most tagged with `tag`, e.g. `progressive metal`.
+    pub fn tag_top_albums<'a>(&'a self, tag: impl Into<Cow<'a, str>>) -> GetTagTopAlbums<'a> {
+        GetTagTopAlbums::new(self, tag)
+    }
+
+    /// The artists most tagged with `tag`, e.g. `progressive metal`.
+    pub fn tag_top_artists<'a>(&'a self, tag: impl Into<Cow<'a, str>>) -> GetTagTopArtists<'a> {
+        GetTagTopArtists::new(self, tag)
+    }
+
+    /// The description of `tag`, e.g. `progressive metal`.
+    pub fn tag_info<'a>(&'a self, tag: impl Into<Cow<'a, str>>) -> GetTagInfo<'a> {
+        GetTagInfo::new(self, tag)
+    }
+
+    /// Tracks similar to `track` by `artist`, most similar first.
+    pub fn similar_tracks<'a>(
+        &'a self,
+        artist: impl Into<Cow<'a, str>>,
+        track: impl Into<Cow<'a, str>>,
+    ) -> GetSimilarTracks<'a> {
+        GetSimilarTracks::new(self, artist, track)
+    }
+
+    /// The name Last.fm corrects `artist` to, e.g. "Guns N' Roses" for "Guns and Roses". `None`
+    /// when it knows no correction.
+    pub async fn artist_correction(&self, artist: &str) -> Result<Option<ShortArtist>, Error> {
+        let corrections = GetArtistCorrection::new(self, artist).send().await?;
+        Ok(corrections.correction.map(|correction| correction.artist))
+    }
+
+    /// Artists similar to `artist`, most similar first.
+    pub fn similar_artists<'a>(&'a self, artist: impl Into<Cow<'a, str>>) -> GetSimilarArtists<'a> {
+        GetSimilarArtists::new(self, artist)
+    }
+
+    /// Artists whose name matches `artist`, best matches first. Page through the results with
+    /// `page` and `limit`.
+    pub fn search_artists<'a>(&'a self, artist: impl Into<Cow<'a, str>>) -> SearchArtists<'a> {
+        SearchArtists::new(self, artist)
+    }
+
+    /// Details of `artist`, like their biography and similar artists.
+    pub fn artist_info<'a>(&'a self, artist: impl Into<Cow<'a, str>>) -> GetArtistInfo<'a> {
+        GetArtistInfo::new(self, artist)
+    }
+
+    /// Tracks whose name matches `track`, best matches first. Page through the results with
+    /// `page` and `limit`.
+    pub fn search_tracks<'a>(&'a self, track: impl Into<Cow<'a, str>>) -> SearchTracks<'a> {
+        SearchTracks::new(self, track)
+    }
+
+    /// Details of `track` by `artist`, like its length and the album it is on.
+    pub fn track_info<'a>(
+        &'a self,
+        artist: impl Into<Cow<'a, str>>,
+        track: impl Into<Cow<'a, str>>,
+    ) -> GetTrackInfo<'a> {
+        GetTrackInfo::new(self, artist, track)
+    }
+
+    /// Albums whose name matches `album`, best matches first. Page through the results with
+    /// `page` and `limit`.
+    pub fn search_albums<'a>(&'a self, album: impl Into<Cow<'a, str>>) -> SearchAlbums<'a> {
+        SearchAlbums::new(self, album)
+    }
+
+    /// Details of `album` by `artist`, like its track list and wiki article.
+    pub fn album_info<'a>(
+        &'a self,
+        artist: impl Into<Cow<'a, str>>,
+        album: impl Into<Cow<'a, str>>,
+    ) -> GetAlbumInfo<'a> {
+        GetAlbumInfo::new(self, artist, album)
+    }
+
+    pub fn top_artists_charts<'a>(&'a self) -> GetTopArtistsCharts<'a> {
+        GetTopArtistsCharts::new(self)
+    }
+
+    pub fn top_tags_charts<'a>(&'a self) -> GetTopTagsCharts<'a> {
+        GetTopTagsCharts::new(self)
+    }
+
+    pub fn top_tracks_charts<'a>(&'a self) -> GetTopTracksCharts<'a> {
+        GetTopTracksCharts::new(self)
+    }
+
+    /// The most listened artists in `country`, named as in ISO 3166-1, e.g. `Germany`.
+    pub fn geo_top_artists<'a>(&'a self, country: impl Into<Cow<'a, str>>) -> GetGeoTopArtists<'a> {
+        GetGeoTopArtists::new(self, country)
+    }
+
+    /// The most listened tracks in `country`, named as in ISO 3166-1, e.g. `Germany`.
+    pub fn geo_top_tracks<'a>(&'a self, country: impl Into<Cow<'a, str>>) -> GetGeoTopTracks<'a> {
+        GetGeoTopTracks::new(self, country)
+    }
+}
+
+request_builder! {
+    struct GetTopAlbums<'a> {
+        method: methods::USER_GET_TOP_ALBUMS,
+        required: {
+            user: Cow<'a, str>,
+        }
+        optional: {
+            /// The time period over which to retrieve top artists for.
+            period: api_types::Period,
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+            /// The number of results to fetch per page. Defaults to 50.
+            limit: usize,
+        }
+    }
+    => api_types::GetTopAlbumsResponse
+    => TopAlbums
+}
+
+request_builder! {
+    struct GetTopTracks<'a> {
+        method: methods::USER_GET_TOP_TRACKS,
+        required: {
+            user: Cow<'a, str>,
+        }
+        optional: {
+            /// The time period over which to retrieve top tracks for.
+            period: api_types::Period,
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+            /// The number of results to fetch per page. Defaults to 50.
+            limit: usize,
+        }
+    }
+    => api_types::GetTopTracksResponse
+    => TopTracks
+}
+
+request_builder! {
+    struct GetTopArtists<'a> {
+        method: methods::USER_GET_TOP_ARTISTS,
+        required: {
+            user: Cow<'a, str>,
+        }
+        optional: {
+            /// The time period over which to retrieve top artists for.
+            period: api_types::Period,
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+            /// The number of results to fetch per page. Defaults to 50.
+            limit: usize,
+        }
+    }
+    => api_types::GetTopArtistsResponse
+    => TopArtists
+}
+
+request_builder! {
+    struct GetWeeklyAlbumChart<'a> {
+        method: methods::USER_GET_WEEKLY_ALBUM_CHART,
+        required: {
+            user: Cow<'a, str>,
+        }
+        optional: {
+            /// Start of the chart as a unix timestamp.
+            from: i64,
+            /// End of the chart as a unix timestamp.
+            to: i64,
+        }
+    }
+    => api_types::GetWeeklyAlbumChartResponse
+    => WeeklyAlbumChart
+}
+
+request_builder! {
+    struct GetWeeklyChartList<'a> {
+        method: methods::USER_GET_WEEKLY_CHART_LIST,
+        required: {
+            user: Cow<'a, str>,
+        }
+        optional: { }
+    }
+    => api_types::GetWeeklyChartListResponse
+    => WeeklyChartList
+}
+
+request_builder! {
+    struct GetRecentTracks<'a> {
+        method: methods::USER_GET_RECENT_TRACKS,
+        required: {
+            user: Cow<'a, str>,
+        }
+        optional: {
+            /// Only include scrobbles after this unix timestamp.
+            from: i64,
+            /// Only include scrobbles before this unix timestamp.
+            to: i64,
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+            /// The number of results to fetch per page. Defaults to 50.
+            limit: usize,
+            /// `1` to also fetch artist details and whether the user loved each track.
+            extended: u8,
+        }
+    }
+    => api_types::GetRecentTracksResponse
+    => RecentTracks
+}
+
+request_builder! {
+    struct GetLovedTracks<'a> {
+        method: methods::USER_GET_LOVED_TRACKS,
+        required: {
+            user: Cow<'a, str>,
+        }
+        optional: {
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+            /// The number of results to fetch per page. Defaults to 50.
+            limit: usize,
+        }
+    }
+    => api_types::GetLovedTracksResponse
+    => LovedTracks
+}
+
+request_builder! {
+    struct GetPersonalTagAlbums<'a> {
+        method: methods::USER_GET_PERSONAL_TAGS,
+        required: {
+            user: Cow<'a, str>,
+            tag: Cow<'a, str>,
+            // always `album`, the other types have differently shaped responses
+            taggingtype: Cow<'a, str>,
+        }
+        optional: {
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+            /// The number of results to fetch per page. Defaults to 50.
+            limit: usize,
+        }
+    }
+    => api_types::GetPersonalTagAlbumsResponse
+    => PersonalTagAlbums
+}
+
+request_builder! {
+    struct GetUserInfo<'a> {
+        method: methods::USER_GET_INFO,
+        required: {
+            user: Cow<'a, str>,
+        }
+        optional: { }
+    }
+    => api_types::GetUserInfoResponse
+    => UserSummary
+}
+
+request_builder! {
+    struct GetFriends<'a> {
+        method: methods::USER_GET_FRIENDS,
+        required: {
+            user: Cow<'a, str>,
+        }
+        optional: {
+            /// `1` to include what each friend listened to last, which [`api_types::UserSummary`] ignores.
+            recenttracks: u8,
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+            /// The number of results to fetch per page. Defaults to 50.
+            limit: usize,
+        }
+    }
+    => api_types::GetFriendsResponse
+    => Friends
+}
+
+request_builder! {
+    struct GetTagTopAlbums<'a> {
+        method: methods::TAG_GET_TOP_ALBUMS,
+        required: {
+            tag: Cow<'a, str>,
+        }
+        optional: {
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+            /// The number of results to fetch per page. Defaults to 50.
+            limit: usize,
+        }
+    }
+    => api_types::GetTagTopAlbumsResponse
+    => TagTopAlbums
+}
+
+request_builder! {
+    struct GetTagTopArtists<'a> {
+        method: methods::TAG_GET_TOP_ARTISTS,
+        required: {
+            tag: Cow<'a, str>,
+        }
+        optional: {
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+            /// The number of results to fetch per page. Defaults to 50.
+            limit: usize,
+        }
+    }
+    => api_types::GetTagTopArtistsResponse
+    => TagTopArtists
+}
+
+request_builder! {
+    struct GetTagInfo<'a> {
+        method: methods::TAG_GET_INFO,
+        required: {
+            tag: Cow<'a, str>,
+        }
+        optional: {
+            /// The language of the wiki as an ISO 639 alpha-2 code.
+            lang: &'a str,
+        }
+    }
+    => api_types::GetTagInfoResponse
+    => TagInfo
+}
+
+request_builder! {
+    struct GetSimilarTracks<'a> {
+        method: methods::TRACK_GET_SIMILAR,
+        required: {
+            artist: Cow<'a, str>,
+            track: Cow<'a, str>,
+        }
+        optional: {
+            /// Look the track up by its MusicBrainz id instead of artist and name.
+            mbid: &'a str,
+            /// The number of similar tracks to fetch.
+            limit: usize,
+            /// `1` to correct misspelled artist and track names.
+            autocorrect: u8,
+        }
+    }
+    => api_types::GetSimilarTracksResponse
+    => SimilarTracks
+}
+
+request_builder! {
+    struct GetSimilarArtists<'a> {
+        method: methods::ARTIST_GET_SIMILAR,
+        required: {
+            artist: Cow<'a, str>,
+        }
+        optional: {
+            /// Look the artist up by its MusicBrainz id instead of its name.
+            mbid: &'a str,
+            /// The number of similar artists to fetch.
+            limit: usize,
+            /// `1` to correct a misspelled artist name.
+            autocorrect: u8,
+        }
+    }
+    => api_types::GetSimilarArtistsResponse
+    => SimilarArtists
+}
+
+request_builder! {
+    struct GetArtistCorrection<'a> {
+        method: methods::ARTIST_GET_CORRECTION,
+        required: {
+            artist: Cow<'a, str>,
+        }
+        optional: { }
+    }
+    => api_types::GetArtistCorrectionResponse
+    => ArtistCorrection
+}
+
+request_builder! {
+    struct GetArtistInfo<'a> {
+        method: methods::ARTIST_GET_INFO,
+        required: {
+            artist: Cow<'a, str>,
+        }
+        optional: {
+            /// Look the artist up by their MusicBrainz id instead of their name.
+            mbid: &'a str,
+            /// The language of the biography as an ISO 639 alpha-2 code.
+            lang: &'a str,
+            /// `1` to correct misspelled artist names.
+            autocorrect: u8,
+            /// Include the play count of this user in the stats.
+            username: &'a str,
+        }
+    }
+    => api_types::GetArtistInfoResponse
+    => ArtistInfo
+}
+
+request_builder! {
+    struct SearchTracks<'a> {
+        method: methods::TRACK_SEARCH,
+        required: {
+            track: Cow<'a, str>,
+        }
+        optional: {
+            /// Only find tracks by this artist.
+            artist: &'a str,
+            /// The number of results to fetch per page. Defaults to 30.
+            limit: usize,
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+        }
+    }
+    => api_types::SearchTracksResponse
+    => TrackSearchResults
+}
+
+request_builder! {
+    struct GetTrackInfo<'a> {
+        method: methods::TRACK_GET_INFO,
+        required: {
+            artist: Cow<'a, str>,
+            track: Cow<'a, str>,
+        }
+        optional: {
+            /// Look the track up by its MusicBrainz id instead of artist and name.
+            mbid: &'a str,
+            /// `1` to correct misspelled artist and track names.
+            autocorrect: u8,
+            /// Include the play count and loved status of this user in the response.
+            username: &'a str,
+        }
+    }
+    => api_types::GetTrackInfoResponse
+    => TrackInfo
+}
+
+request_builder! {
+    struct SearchArtists<'a> {
+        method: methods::ARTIST_SEARCH,
+        required: {
+            artist: Cow<'a, str>,
+        }
+        optional: {
+            /// The number of results to fetch per page. Defaults to 30.
+            limit: usize,
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+        }
+    }
+    => api_types::SearchArtistsResponse
+    => ArtistSearchResults
+}
+
+request_builder! {
+    struct SearchAlbums<'a> {
+        method: methods::ALBUM_SEARCH,
+        required: {
+            album: Cow<'a, str>,
+        }
+        optional: {
+            /// The number of results to fetch per page. Defaults to 30.
+            limit: usize,
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+        }
+    }
+    => api_types::SearchAlbumsResponse
+    => AlbumSearchResults
+}
+
+request_builder! {
+    struct GetAlbumInfo<'a> {
+        method: methods::ALBUM_GET_INFO,
+        required: {
+            artist: Cow<'a, str>,
+            album: Cow<'a, str>,
+        }
+        optional: {
+            /// Look the album up by its MusicBrainz id instead of artist and name.
+            mbid: &'a str,
+            /// `1` to correct misspelled artist and album names.
+            autocorrect: u8,
+            /// Include the play count of this user in the response.
+            username: &'a str,
+            /// The language of the wiki as an ISO 639 alpha-2 code.
+            lang: &'a str,
+        }
+    }
+    => api_types::GetAlbumInfoResponse
+    => AlbumInfo
+}
+
+request_builder! {
+    struct GetTopArtistsCharts<'a> {
+        method: methods::CHART_GET_TOP_ARTISTS,
+        required: { }
+        optional: {
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+            /// The number of results to fetch per page. Defaults to 50.
+            limit: usize,
+        }
+    }
+    => api_types::chart::GetTopArtistsResponse
+    => api_types::chart::TopArtists
+}
+
+request_builder! {
+    struct GetTopTagsCharts<'a> {
+        method: methods::CHART_GET_TOP_TAGS,
+        required: { }
+        optional: {
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+            /// The number of results to fetch per page. Defaults to 50.
+            limit: usize,
+        }
+    }
+    => api_types::chart::GetTopTagsResponse
+    => api_types::chart::TopTags
+}
+
+request_builder! {
+    struct GetTopTracksCharts<'a> {
+        method: methods::CHART_GET_TOP_TRACKS,
+        required: { }
+        optional: {
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+            /// The number of results to fetch per page. Defaults to 50.
+            limit: usize,
+        }
+    }
+    => api_types::chart::GetTopTracksResponse
+    => api_types::chart::TopTracks
+}
+
+request_builder! {
+    struct GetGeoTopArtists<'a> {
+        method: methods::GEO_GET_TOP_ARTISTS,
+        required: {
+            country: Cow<'a, str>,
+        }
+        optional: {
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+            /// The number of results to fetch per page. Defaults to 50.
+            limit: usize,
+        }
+    }
+    => api_types::geo::GetTopArtistsResponse
+    => api_types::geo::TopArtists
+}
+
+request_builder! {
+    struct GetGeoTopTracks<'a> {
+        method: methods::GEO_GET_TOP_TRACKS,
+        required: {
+            country: Cow<'a, str>,
+        }
+        optional: {
+            /// The page number to fetch. Defaults to first page.
+            page: usize,
+            /// The number of results to fetch per page. Defaults to 50.
+            limit: usize,
+        }
+    }
+    => api_types::geo::GetTopTracksResponse
+    => api_types::geo::TopTracks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{top_albums, top_albums_pages};
+
+    use pretty_assertions::assert_eq;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{header, method, query_param},
+    };
+
+    async fn mock_client(method_name: &str, body: &str) -> (MockServer, Client) {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("method", method_name))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body))
+            .mount(&server)
+            .await;
+        let client = Client::builder("secret".into(), "apikey".into())
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        (server, client)
+    }
+
+    #[test]
+    fn signs_raw_values_in_order() {
+        let expected = "729a7dff46bbfb63f7ee61f9dfcd97df";
+        let mut args = [
+            ("tag", "métal"),
+            ("artist", "Zeal & Ardor"),
+            ("method", "artist.getInfo"),
+            ("tag", "avant-garde"),
+            ("api_key", "apikey"),
+        ];
+        assert_eq!(sign(&mut args, "secret"), expected);
+        assert_eq!(
+            args,
+            [
+                ("api_key", "apikey"),
+                ("artist", "Zeal & Ardor"),
+                ("method", "artist.getInfo"),
+                ("tag", "avant-garde"),
+                ("tag", "métal"),
+            ]
+        );
+
+        args.reverse();
+        assert_eq!(sign(&mut args, "secret"), expected);
+        let mut encoded = args.map(|(k, v)| (k, if k == "artist" { "Zeal+%26+Ardor" } else { v }));
+        assert_ne!(sign(&mut encoded, "secret"), expected);
+    }
+
+    #[test]
+    fn rejects_empty_credentials() {
+        assert!(matches!(
+            Client::new("secret".into(), String::new()),
+            Err(Error::MissingCredential("api key"))
+        ));
+        assert!(matches!(
+            Client::builder(String::new(), "apikey".into()).build(),
+            Err(Error::MissingCredential("shared secret"))
+        ));
+    }
+
+    #[test]
+    fn hex_signature_matches_formatting() {
+        fn formatted(args: &mut [(&str, &str)], shared_secret: &str) -> String {
+            use std::fmt::Write;
+
+            args.sort_unstable();
+            let mut hasher = Md5::new();
+            for (k, v) in args.iter() {
+                hasher.update(k);
+                hasher.update(v);
+            }
+            hasher.update(shared_secret);
+            let mut signature = String::new();
+            for byte in hasher.finalize() {
+                let _ = write!(&mut signature, "{byte:02x}");
+            }
+            signature
+        }
+
+        for secret in [
+            "",
+            "secret",
+            "ünïcödé",
+            "a much longer shared secret than usual",
+        ] {
+            let args = [
+                ("method", "user.getTopAlbums"),
+                ("user", secret),
+                ("api_key", "apikey"),
+            ];
+            assert_eq!(
+                sign(&mut args.clone(), secret),
+                formatted(&mut args.clone(), secret)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_encoded_values_with_raw_signature() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("artist", "Zeal & Ardor"))
+            .and(query_param("track", "Gravedigger’s Chant"))
+            .and(query_param("api_sig", "34eb4473e3b33ba01f32702a2e17d745"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<lfm status="ok"><similartracks track="Gravedigger’s Chant" artist="Zeal &amp; Ardor"></similartracks></lfm>"#,
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::builder("secret".into(), "apikey".into())
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+
+        let similar = client
+            .similar_tracks("Zeal & Ardor", "Gravedigger’s Chant")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(similar.artist, "Zeal & Ardor");
+
+        let requests = server.received_requests().await.unwrap();
+        let query = requests[0].url.query().unwrap();
+        assert!(query.contains("artist=Zeal+%26+Ardor"), "{query}");
+    }
+
+    #[tokio::test]
+    async fn dropped_client_leaves_no_tasks() {
+        let (server, client) = mock_client(
+            "user.getInfo",
+            r#"<lfm status="ok"><user><name>RJ</name></user></lfm>"#,
+        )
+        .await;
+        let metrics = tokio::runtime::Handle::current().metrics();
+        let before = metrics.num_alive_tasks();
+
+        client.validate_session("sk").await.unwrap();
+        assert!(
+            metrics.num_alive_tasks() > before,
+            "the pooled connection is kept alive"
+        );
+        drop(client);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while metrics.num_alive_tasks() > before {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("the tasks of the client stop once it is dropped");
+        drop(server);
+    }
+
+    #[tokio::test]
+    async fn signs_with_overridden_credentials() {
+        let (server, client) = mock_client(
+            "track.getSimilar",
+            r#"<lfm status="ok"><similartracks track="Vector" artist="Haken"></similartracks></lfm>"#,
+        )
+        .await;
+
+        client
+            .similar_tracks("Haken", "Vector")
+            .credentials("tenant-key", "tenant-secret")
+            .send()
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let pairs: Vec<_> = requests[0].url.query_pairs().collect();
+        let mut args: Vec<_> = pairs
+            .iter()
+            .filter(|(key, _)| key != "api_sig")
+            .map(|(key, value)| (key.as_ref(), value.as_ref()))
+            .collect();
+        assert!(args.contains(&("api_key", "tenant-key")));
+        let signature = pairs.iter().find(|(key, _)| key == "api_sig").unwrap();
+        assert_eq!(signature.1, sign(&mut args, "tenant-secret"));
+        assert_ne!(signature.1, sign(&mut args, "secret"));
+    }
+
+    #[tokio::test]
+    async fn artist_correction() {
+        let (_server, client) = mock_client(
+            "artist.getCorrection",
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<lfm status="ok">
+  <corrections>
+    <correction index="0">
+      <artist>
+        <name>Guns N' Roses</name>
+        <mbid>eeb1195b-f213-4ce1-b28c-8565211f8e43</mbid>
+        <url>https://www.last.fm/music/Guns+N%27+Roses</url>
+      </artist>
+    </correction>
+  </corrections>
+</lfm>"#,
+        )
+        .await;
+        let artist = client.artist_correction("guns and roses").await.unwrap();
+        assert_eq!(
+            artist,
+            Some(ShortArtist {
+                name: "Guns N' Roses".into(),
+                mbid: "eeb1195b-f213-4ce1-b28c-8565211f8e43".into(),
+                url: "https://www.last.fm/music/Guns+N%27+Roses".parse().unwrap(),
+                images: Vec::new(),
+            })
+        );
+
+        for empty in [
+            r#"<lfm status="ok"><corrections/></lfm>"#,
+            r#"<lfm status="ok"><corrections>
+</corrections></lfm>"#,
+        ] {
+            let (_server, client) = mock_client("artist.getCorrection", empty).await;
+            let artist = client.artist_correction("Haken").await.unwrap();
+            assert_eq!(artist, None);
+        }
+    }
+
+    #[tokio::test]
+    async fn connection_options() {
+        use std::io::Read;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut preface = [0; 24];
+            stream.read_exact(&mut preface).unwrap();
+            preface
+        });
+        let client = Client::builder("secret".into(), "apikey".into())
+            .base_url(base_url)
+            .http2_prior_knowledge()
+            .pool_idle_timeout(Duration::from_secs(5))
+            .tcp_keepalive(None)
+            .build()
+            .unwrap();
+
+        // the server hangs up without answering, so the request is only started
+        let request = tokio::spawn(async move { client.top_albums("RJ").send().await });
+        let preface = tokio::task::spawn_blocking(move || server.join().unwrap())
+            .await
+            .unwrap();
+        request.abort();
+        assert_eq!(&preface, b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n");
+    }
+
+    #[tokio::test]
+    async fn json_format() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("method", "user.getTopAlbums"))
+            .and(query_param("format", "json"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"topalbums":{"album":[],"@attr":{"user":"RJ","page":"1","perPage":"50","totalPages":"0","total":"0"}}}"#,
+            ))
+            .mount(&server)
+            .await;
+        let client = Client::builder("secret".into(), "apikey".into())
+            .base_url(server.uri())
+            .format(Format::Json)
+            .build()
+            .unwrap();
+
+        let top = client.top_albums("RJ").send().await.unwrap();
+        assert_eq!((top.user.as_str(), top.total), ("RJ", 0));
+
+        let requests = server.received_requests().await.unwrap();
+        let pairs: Vec<_> = requests[0].url.query_pairs().collect();
+        let mut args: Vec<_> = pairs
+            .iter()
+            .filter(|(key, _)| key != "api_sig" && key != "format")
+            .map(|(key, value)| (key.as_ref(), value.as_ref()))
+            .collect();
+        let signature = pairs.iter().find(|(key, _)| key == "api_sig").unwrap();
+        assert_eq!(signature.1, sign(&mut args, "secret"));
+    }
+
+    #[tokio::test]
+    async fn prepared_request_is_sent_to_its_signed_url() {
+        let (server, client) = mock_client(
+            "track.getSimilar",
+            r#"<lfm status="ok"><similartracks track="Vector" artist="Haken"></similartracks></lfm>"#,
+        )
+        .await;
+
+        let prepared = client.similar_tracks("Haken", "Vector").limit(5).prepare();
+        assert_eq!(prepared.method(), "track.getSimilar");
+        assert_eq!(
+            prepared.args().collect::<Vec<_>>(),
+            [("artist", "Haken"), ("track", "Vector"), ("limit", "5")]
+        );
+        // sent twice, the way a retry would
+        for _ in 0..2 {
+            prepared.send().await.unwrap();
+        }
+
+        let url = prepared.signed_url().unwrap();
+        assert!(url.as_str().starts_with(&server.uri()));
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 2);
+        // the mock server knows the host only as far as the request tells it, so the queries are
+        // compared
+        assert!(
+            requests
+                .iter()
+                .all(|request| request.url.query() == url.query())
+        );
+    }
+
+    async fn retrying_client(failure: &str, failures: u64) -> (MockServer, Client) {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(failure))
+            .up_to_n_times(failures)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<lfm status="ok"><topalbums user="RJ" page="1" perPage="50" totalPages="0" total="0"></topalbums></lfm>"#,
+            ))
+            .mount(&server)
+            .await;
+        let client = Client::builder("secret".into(), "apikey".into())
+            .base_url(server.uri())
+            .backoff(Backoff::new(Duration::from_millis(1)))
+            .max_retries(3)
+            .build()
+            .unwrap();
+        (server, client)
+    }
+
+    #[tokio::test]
+    async fn rate_limited_requests_are_retried() {
+        let (server, client) = retrying_client(
+            r#"<lfm status="failed"><error code="29">Rate Limit Exceeded</error></lfm>"#,
+            2,
+        )
+        .await;
+        let top = client.top_albums("RJ").send().await.unwrap();
+        assert_eq!(top.user, "RJ");
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn other_errors_are_not_retried() {
+        let (server, client) = retrying_client(
+            r#"<lfm status="failed"><error code="6">User not found</error></lfm>"#,
+            2,
+        )
+        .await;
+        let err = client.top_albums("RJ").send().await.unwrap_err();
+        assert!(matches!(err, Error::Api(ref e) if e.code == "6"), "{err}");
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn valid_session() {
+        let (server, client) = mock_client(
+            "user.getInfo",
+            r#"<lfm status="ok"><user><name>RJ</name><realname>Richard Jones</realname></user></lfm>"#,
+        )
+        .await;
+        assert_eq!(client.validate_session("sk").await.unwrap(), "RJ");
+        let requests = server.received_requests().await.unwrap();
+        assert!(
+            requests[0]
+                .url
+                .query_pairs()
+                .any(|(k, v)| k == "sk" && v == "sk")
+        );
+    }
+
+    #[tokio::test]
+    async fn revoked_session() {
+        let (_server, client) = mock_client(
+            "user.getInfo",
+            r#"<lfm status="failed"><error code="9">Invalid session key - Please re-authenticate</error></lfm>"#,
+        )
+        .await;
+        let err = client.validate_session("sk").await.unwrap_err();
+        assert!(matches!(err, Error::InvalidSession), "{err}");
+    }
+
+    #[tokio::test]
+    async fn top_albums_between_uses_weekly_chart() {
+        let (server, client) = mock_client(
+            "user.getWeeklyAlbumChart",
+            r#"<lfm status="ok">
+<weeklyalbumchart user="RJ" from="1114965332" to="1115570132">
+  <album rank="2">
+    <artist mbid="a74b1b7f-71a5-4011-9441-d0b5e4122711">Radiohead</artist>
+    <name>OK Computer</name>
+    <mbid>0b6b4ba0-d36f-47bd-b4ea-6a5b91842d29</mbid>
+    <playcount>12</playcount>
+    <url>https://www.last.fm/music/Radiohead/OK+Computer</url>
+  </album>
+  <album rank="1">
+    <artist mbid="">Dream Theater</artist>
+    <name>Images and Words</name>
+    <mbid></mbid>
+    <playcount>20</playcount>
+    <url>https://www.last.fm/music/Dream+Theater/Images+and+Words</url>
+  </album>
+</weeklyalbumchart>
+</lfm>"#,
+        )
+        .await;
+
+        let albums = client
+            .top_albums_between("RJ", 1114965332, 1115570132)
+            .await
+            .expect("can fetch");
+
+        let requests = server.received_requests().await.unwrap();
+        let query: Vec<_> = requests[0].url.query_pairs().collect();
+        assert!(query.contains(&("from".into(), "1114965332".into())));
+        assert!(query.contains(&("to".into(), "1115570132".into())));
+
+        assert_eq!(
+            albums
+                .iter()
+                .map(|album| (album.artist.name.as_str(), album.name.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("Dream Theater", "Images and Words"),
+                ("Radiohead", "OK Computer")
+            ]
+        );
+        assert_eq!(
+            albums[1].artist.mbid,
+            "a74b1b7f-71a5-4011-9441-d0b5e4122711"
+        );
+    }
+
+    #[tokio::test]
+    async fn album_count_reads_total() {
+        let (server, client) = mock_client(
+            "user.getTopAlbums",
+            r#"<lfm status="ok">
+<topalbums user="RJ" type="overall" page="1" perPage="1" totalPages="1234" total="1234">
+  <album rank="1">
+    <name>Images and Words</name>
+    <playcount>174</playcount>
+    <mbid>f20971f2-c8ad-4d26-91ab-730f6dedafb2</mbid>
+    <url>http://www.last.fm/music/Dream+Theater/Images+and+Words</url>
+    <artist>
+      <name>Dream Theater</name>
+      <mbid>28503ab7-8bf2-4666-a7bd-2644bfc7cb1d</mbid>
+      <url>http://www.last.fm/music/Dream+Theater</url>
+    </artist>
+    <image size="small">...</image>
+  </album>
+</topalbums>
+</lfm>"#,
+        )
+        .await;
+
+        assert_eq!(client.album_count("RJ").await.unwrap(), 1234);
+        assert_eq!(client.album_count("RJ").await.unwrap(), 1234);
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1, "second count is cached");
+        assert!(
+            requests[0]
+                .url
+                .query_pairs()
+                .any(|(k, v)| k == "limit" && v == "1")
+        );
+    }
+
+    #[tokio::test]
+    async fn decodes_gzipped_responses() {
+        use std::io::Write as _;
+
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(
+            br#"<lfm status="ok">
+<session>
+  <name>RJ</name>
+  <key>d580d57f32848f5dcf574d1ce18d78b2</key>
+  <subscriber>0</subscriber>
+</session>
+</lfm>"#,
+        )
+        .unwrap();
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("method", "auth.getSession"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-encoding", "gzip")
+                    .set_body_bytes(gz.finish().unwrap()),
+            )
+            .mount(&server)
+            .await;
+        let client = Client::builder("secret".into(), "apikey".into())
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+
+        let session = client.authenticate("token").await.unwrap();
+        assert_eq!(session.name, "RJ");
+
+        let requests = server.received_requests().await.unwrap();
+        let accept = requests[0].headers.get("accept-encoding").unwrap();
+        assert!(accept.to_str().unwrap().contains("gzip"), "{accept:?}");
+    }
+
+    const NOW_PLAYING: &str = r#"<lfm status="ok">
+<recenttracks user="RJ" page="1" perPage="1" totalPages="1000" total="1000">
+  <track nowplaying="true">
+    <artist mbid="cc0b7089-c08d-4c10-b6b0-873582c17fd6">Haken</artist>
+    <name>Puzzle Box</name>
+    <streamable>0</streamable>
+    <mbid></mbid>
+    <album mbid="">Vector</album>
+    <url>https://www.last.fm/music/Haken/_/Puzzle+Box</url>
+    <image size="small">https://example.com/small.png</image>
+  </track>
+  <track>
+    <artist mbid="cc0b7089-c08d-4c10-b6b0-873582c17fd6">Haken</artist>
+    <name>The Good Doctor</name>
+    <streamable>0</streamable>
+    <mbid></mbid>
+    <album mbid="">Vector</album>
+    <url>https://www.last.fm/music/Haken/_/The+Good+Doctor</url>
+    <image size="small">https://example.com/small.png</image>
+    <date uts="1213031819">9 Jun 2008, 17:16</date>
+  </track>
+</recenttracks>
+</lfm>"#;
+
+    #[tokio::test]
+    async fn now_playing() {
+        let (server, client) = mock_client("user.getRecentTracks", NOW_PLAYING).await;
+
+        let track = client.now_playing("RJ").await.unwrap().expect("is playing");
+        assert_eq!(track.name, "Puzzle Box");
+        assert_eq!(track.artist.name, "Haken");
+        assert_eq!(track.album.name, "Vector");
+        assert_eq!(track.date, None);
+
+        let requests = server.received_requests().await.unwrap();
+        assert!(
+            requests[0]
+                .url
+                .query_pairs()
+                .any(|(k, v)| k == "limit" && v == "1")
+        );
+    }
+
+    #[tokio::test]
+    async fn nothing_playing() {
+        let (_server, client) = mock_client(
+            "user.getRecentTracks",
+            r#"<lfm status="ok">
+<recenttracks user="RJ" page="1" perPage="1" totalPages="1000" total="1000">
+  <track>
+    <artist mbid="">Haken</artist>
+    <name>The Good Doctor</name>
+    <streamable>0</streamable>
+    <mbid></mbid>
+    <album mbid="">Vector</album>
+    <url>https://www.last.fm/music/Haken/_/The+Good+Doctor</url>
+    <date uts="1213031819">9 Jun 2008, 17:16</date>
+  </track>
+</recenttracks>
+</lfm>"#,
+        )
+        .await;
+        assert_eq!(client.now_playing("RJ").await.unwrap(), None);
+
+        let (_server, client) = mock_client(
+            "user.getRecentTracks",
+            r#"<lfm status="ok"><recenttracks user="RJ" page="1" perPage="1" totalPages="0" total="0"></recenttracks></lfm>"#,
+        )
+        .await;
+        assert_eq!(client.now_playing("RJ").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn not_modified_reuses_cached_value() {
+        let server = MockServer::start().await;
+        // an empty body would fail to decode, so this only passes if nothing is re-parsed
+        Mock::given(method("GET"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v1\"")
+                    .set_body_string(NOW_PLAYING),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::builder("secret".into(), "apikey".into())
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+
+        let first = client.recent_tracks("RJ").send().await.unwrap();
+        let second = client.recent_tracks("RJ").send().await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(second.tracks[0].name, "Puzzle Box");
+    }
+
+    #[tokio::test]
+    async fn cached_value_of_another_type_is_not_revalidated() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(0)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v1\"")
+                    .set_body_string(NOW_PLAYING),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        let client = Client::builder("secret".into(), "apikey".into())
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+
+        let args = [("user", "RJ")];
+        client
+            .make_request::<api_types::GetRecentTracksResponse>(
+                methods::USER_GET_RECENT_TRACKS,
+                args,
+                None,
+            )
+            .await
+            .unwrap();
+        client
+            .make_request::<api_types::LfmStatus<IgnoredAny>>(
+                methods::USER_GET_RECENT_TRACKS,
+                args,
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn forgotten_user_is_fetched_again() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v1\"")
+                    .set_body_string(NOW_PLAYING),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        let client = Client::builder("secret".into(), "apikey".into())
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+
+        client.recent_tracks("RJ").send().await.unwrap();
+        client.recent_tracks("RJ").send().await.unwrap();
+        client.forget_user("RJ");
+        client.recent_tracks("RJ").send().await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests[2].headers.get("if-none-match").is_none());
+    }
+
+    #[tokio::test]
+    async fn responses_without_validators_are_not_kept() {
+        let (server, client) = mock_client("user.getRecentTracks", NOW_PLAYING).await;
+        client.recent_tracks("RJ").send().await.unwrap();
+        client.recent_tracks("RJ").send().await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests[1].headers.get("if-none-match").is_none());
+        assert!(requests[1].headers.get("if-modified-since").is_none());
+    }
+
+    #[tokio::test]
+    async fn walks_all_album_pages() {
+        use futures_util::TryStreamExt;
+
+        let server = MockServer::start().await;
+        let pages = top_albums_pages(&[&["Vector", "Virus"], &["Affinity"]]);
+        for (page, body) in (1..).zip(pages) {
+            Mock::given(method("GET"))
+                .and(query_param("method", "user.getTopAlbums"))
+                .and(query_param("page", page.to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_string(body))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        let client = Client::builder("secret".into(), "apikey".into())
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+
+        let pages: Vec<_> = client
+            .top_albums_pages("RJ", api_types::Period::Overall)
+            .try_collect()
+            .await
+            .unwrap();
+        let names: Vec<_> = pages
+            .iter()
+            .flat_map(|page| &page.albums)
+            .map(|album| album.name.as_str())
+            .collect();
+        assert_eq!(names, ["Vector", "Virus", "Affinity"]);
+    }
+
+    #[tokio::test]
+    async fn spawned_request_owns_user() {
+        let (_server, client) = mock_client("user.getTopAlbums", &top_albums(&["Vector"])).await;
+        let client: &'static Client = Box::leak(Box::new(client));
+
+        let user = String::from("RJ");
+        let albums = tokio::spawn(client.top_albums(user).send())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(albums.albums[0].name, "Vector");
+    }
+
+    #[tokio::test]
+    async fn raw_get_returns_body() {
+        let body = r#"<lfm status="ok"><event id="1">Haken live</event></lfm>"#;
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("method", "event.getInfo"))
+            .and(query_param("event", "1"))
+            .and(query_param("api_key", "apikey"))
+            .and(header("accept", "application/xml"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::builder("secret".into(), "apikey".into())
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+
+        let raw = client.raw_get("event.getInfo", &[("event", "1")]).await;
+        assert_eq!(raw.unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn method_timeout_overrides_default() {
+        let server = MockServer::start().await;
+        for (name, body) in [
+            ("user.getTopAlbums", top_albums(&["Vector"])),
+            ("user.getRecentTracks", NOW_PLAYING.to_owned()),
+        ] {
+            Mock::given(method("GET"))
+                .and(query_param("method", name))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_string(body)
+                        .set_delay(Duration::from_millis(200)),
+                )
+                .mount(&server)
+                .await;
+        }
+        let client = Client::builder("secret".into(), "apikey".into())
+            .base_url(server.uri())
+            .timeout(Duration::from_secs(5))
+            .method_timeout(methods::USER_GET_TOP_ALBUMS, Duration::from_millis(50))
+            .build()
+            .unwrap();
+
+        let albums = client.top_albums("RJ").send().await;
+        assert!(matches!(albums, Err(Error::Http(e)) if e.is_timeout()));
+        let track = client.now_playing("RJ").await.unwrap();
+        assert!(track.is_some());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn logs_slow_requests() {
+        #[derive(Clone, Default)]
+        struct Capture(Arc<std::sync::Mutex<Vec<u8>>>);
+
+        impl std::io::Write for Capture {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(top_albums(&["Vector"]))
+                    .set_delay(Duration::from_millis(200)),
+            )
+            .mount(&server)
+            .await;
+        let client = Client::builder("secret".into(), "apikey".into())
+            .base_url(server.uri())
+            .slow_request_threshold(Duration::from_millis(100))
+            .build()
+            .unwrap();
+
+        let logs = Capture::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        client.top_albums("RJ").send().await.unwrap();
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("WARN"), "{logs}");
+        assert!(logs.contains("slow Last.fm request"), "{logs}");
+        assert!(logs.contains("method=\"user.getTopAlbums\""), "{logs}");
+    }
+
+    /// A client whose top albums claim five pages, like a stale total, but answer the second one
+    /// with `past_end`.
+    async fn albums_past_end(past_end: &str) -> (MockServer, Client) {
+        let server = MockServer::start().await;
+        let page_one = top_albums_pages(&[&["Vector", "Virus"][..]; 5]).remove(0);
+        for (page, body) in [("1", page_one.as_str()), ("2", past_end)] {
+            Mock::given(method("GET"))
+                .and(query_param("page", page))
+                .respond_with(ResponseTemplate::new(200).set_body_string(body))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        let client = Client::builder("secret".into(), "apikey".into())
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        (server, client)
+    }
+
+    #[tokio::test]
+    async fn empty_page_ends_album_pages() {
+        use futures_util::TryStreamExt;
+
+        let (_server, client) =
+            albums_past_end(&top_albums_pages(&[&["Vector", "Virus"], &[]])[1]).await;
+        let pages: Vec<_> = client
+            .top_albums_pages("RJ", api_types::Period::Overall)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].albums.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn out_of_range_page_ends_album_pages() {
+        use futures_util::TryStreamExt;
+
+        let (_server, client) = albums_past_end(
+            r#"<lfm status="failed"><error code="6">Invalid parameters</error></lfm>"#,
+        )
+        .await;
+        let pages: Vec<_> = client
+            .top_albums_pages("RJ", api_types::Period::Overall)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(pages.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn other_errors_surface_from_album_pages() {
+        use futures_util::TryStreamExt;
+
+        let (_server, client) = albums_past_end(
+            r#"<lfm status="failed"><error code="8">Operation failed</error></lfm>"#,
+        )
+        .await;
+        let res: Result<Vec<_>, _> = client
+            .top_albums_pages("RJ", api_types::Period::Overall)
+            .try_collect()
+            .await;
+        assert!(matches!(res, Err(Error::Api(e)) if e.code == "8"));
+    }
+}
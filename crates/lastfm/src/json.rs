@@ -0,0 +1,232 @@
+//! Decoding the JSON flavor of Last.fm responses, requested with `format=json`.
+//!
+//! The types in [`api_types`](crate::api_types) follow the XML responses. JSON spells XML
+//! attributes as an `@attr` object, or as plain keys next to a `#text` key that holds the text.
+//! Rather than keeping a second set of types in sync, a JSON body is turned back into the XML
+//! Last.fm would have sent and decoded from that.
+
+use std::borrow::Cow;
+
+use quick_xml::{DeError, escape::escape};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::{BODY_EXCERPT_LEN, Error, decode, excerpt, is_maintenance_page, strip_bom};
+
+/// Like [`decode`], for a body requested with `format=json`.
+pub fn decode_json<T>(body: &str) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let body = strip_bom(body);
+    let value: Value = serde_json::from_str(body).map_err(|e| {
+        if is_maintenance_page(body) {
+            return Error::Maintenance;
+        }
+        let e = DeError::Custom(e.to_string());
+        Error::Decoding(e, excerpt(body, BODY_EXCERPT_LEN).to_owned())
+    })?;
+    decode(&to_xml(&value)).map_err(|e| match e {
+        // the excerpt of the translation would not match what Last.fm sent
+        Error::Decoding(e, _) => Error::Decoding(e, excerpt(body, BODY_EXCERPT_LEN).to_owned()),
+        e => e,
+    })
+}
+
+/// The `<lfm>` document `value` stands for.
+fn to_xml(value: &Value) -> String {
+    let mut xml = String::new();
+    let Value::Object(root) = value else {
+        return xml;
+    };
+    if let Some(code) = root.get("error") {
+        let message = root.get("message").map(text).unwrap_or_default();
+        xml.push_str(r#"<lfm status="failed"><error code=""#);
+        xml.push_str(&escape(text(code)));
+        xml.push_str(r#"">"#);
+        xml.push_str(&escape(message));
+        xml.push_str("</error></lfm>");
+        return xml;
+    }
+    xml.push_str(r#"<lfm status="ok">"#);
+    for (name, value) in root {
+        element(&mut xml, name, value);
+    }
+    xml.push_str("</lfm>");
+    xml
+}
+
+/// Append `value` as one `<name>` element, or one per item for arrays.
+fn element(xml: &mut String, name: &str, value: &Value) {
+    match value {
+        Value::Null => {}
+        Value::Array(items) => {
+            for item in items {
+                element(xml, name, item);
+            }
+        }
+        Value::Object(fields) => {
+            let content = fields.get("#text");
+            xml.push('<');
+            xml.push_str(name);
+            for (key, value) in attributes(fields, content.is_some()) {
+                xml.push(' ');
+                xml.push_str(key);
+                xml.push_str(r#"=""#);
+                xml.push_str(&escape(text(value)));
+                xml.push('"');
+            }
+            xml.push('>');
+            if let Some(content) = content {
+                xml.push_str(&escape(text(content)));
+            } else {
+                for (key, value) in fields.iter().filter(|(key, _)| *key != "@attr") {
+                    element(xml, key, value);
+                }
+            }
+            xml.push_str("</");
+            xml.push_str(name);
+            xml.push('>');
+        }
+        value => {
+            xml.push('<');
+            xml.push_str(name);
+            xml.push('>');
+            xml.push_str(&escape(text(value)));
+            xml.push_str("</");
+            xml.push_str(name);
+            xml.push('>');
+        }
+    }
+}
+
+/// The attributes of an element: those in its `@attr` object, and when it has a `#text` its
+/// other keys, like the `size` of `{"size": "small", "#text": "https://..."}`.
+fn attributes(
+    fields: &Map<String, Value>,
+    has_content: bool,
+) -> impl Iterator<Item = (&str, &Value)> {
+    let nested = match fields.get("@attr") {
+        Some(Value::Object(attrs)) => Some(attrs),
+        _ => None,
+    };
+    let inline = fields
+        .iter()
+        .filter(move |(key, _)| has_content && *key != "#text" && *key != "@attr");
+    nested
+        .into_iter()
+        .flatten()
+        .chain(inline)
+        .map(|(key, value)| (key.as_str(), value))
+}
+
+/// The text of a scalar. Last.fm sends most numbers as strings already.
+fn text(value: &Value) -> Cow<'_, str> {
+    match value {
+        Value::String(s) => s.into(),
+        Value::Bool(true) => "1".into(),
+        Value::Bool(false) => "0".into(),
+        Value::Number(n) => n.to_string().into(),
+        _ => "".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::api_types::{
+        Album, GetTopAlbumsResponse, Image, ImageSize, LfmStatus, Period, ShortArtist, TopAlbums,
+    };
+
+    #[test]
+    fn top_albums() {
+        let x: GetTopAlbumsResponse = decode_json(
+            r##"{
+  "topalbums": {
+    "album": [
+      {
+        "artist": {
+          "url": "https://www.last.fm/music/Haken",
+          "name": "Haken",
+          "mbid": "5b1a9a6c-1f6e-4a56-8c6e-ff8e3d45c9a3"
+        },
+        "image": [
+          { "size": "small", "#text": "https://lastfm.freetls.fastly.net/i/u/34s/vector.png" },
+          { "size": "extralarge", "#text": "" }
+        ],
+        "mbid": "",
+        "url": "https://www.last.fm/music/Haken/Vector",
+        "playcount": "174",
+        "@attr": { "rank": "1" },
+        "name": "Vector & Virus"
+      }
+    ],
+    "@attr": {
+      "user": "RJ",
+      "type": "overall",
+      "totalPages": "12",
+      "page": "1",
+      "perPage": "1",
+      "total": "12"
+    }
+  }
+}"##,
+        )
+        .expect("can parse");
+        assert_eq!(
+            x,
+            LfmStatus::Ok(TopAlbums {
+                user: "RJ".into(),
+                period: Some(Period::Overall),
+                page: 1,
+                per_page: 1,
+                total_pages: 12,
+                total: 12,
+                albums: vec![Album {
+                    rank: Some(1),
+                    name: "Vector & Virus".into(),
+                    playcount: 174,
+                    mbid: String::new(),
+                    url: "https://www.last.fm/music/Haken/Vector".parse().unwrap(),
+                    artist: ShortArtist {
+                        name: "Haken".into(),
+                        mbid: "5b1a9a6c-1f6e-4a56-8c6e-ff8e3d45c9a3".into(),
+                        url: "https://www.last.fm/music/Haken".parse().unwrap(),
+                        images: Vec::new(),
+                    },
+                    images: vec![
+                        Image {
+                            size: ImageSize::Small,
+                            url: "https://lastfm.freetls.fastly.net/i/u/34s/vector.png".into(),
+                        },
+                        Image {
+                            size: ImageSize::Extralarge,
+                            url: String::new(),
+                        },
+                    ],
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn failed() {
+        let x: GetTopAlbumsResponse =
+            decode_json("\u{feff}{\"error\": 6, \"message\": \"User not found\"}")
+                .expect("can parse");
+        let error = x.into_result().unwrap_err();
+        assert_eq!(
+            (error.code.as_str(), error.message.as_str()),
+            ("6", "User not found")
+        );
+    }
+
+    #[test]
+    fn not_json() {
+        let x = decode_json::<GetTopAlbumsResponse>(r#"<lfm status="ok"></lfm>"#);
+        assert!(matches!(x, Err(Error::Decoding(_, body)) if body.starts_with("<lfm")));
+    }
+}
@@ -4,296 +4,239 @@
 //! ## Usage Example
 //!
 //! ```
-//! let client = Client::new(shared_secret, api_key);
+//! # #[cfg(feature = "client")] {
+//! # use std::time::Duration;
+//! # use lastfm::{Client, retry::Backoff};
+//! # let (shared_secret, api_key) = (String::from("secret"), String::from("apikey"));
+//! let client = Client::new(shared_secret.clone(), api_key.clone())?;
+//!
+//! // or, to tweak the defaults
+//! let client = Client::builder(shared_secret, api_key)
+//!     .backoff(Backoff::new(Duration::from_secs(1)))
+//!     .build()?;
+//! # }
+//! # Ok::<(), lastfm::Error>(())
 //! ```
+//!
+//! ## Features
+//!
+//! - `client` (default): the HTTP [`Client`](crate::Client). Without it, only the response types
+//!   in [`api_types`], [`decode`] and [`decode_json`] are available, for parsing responses fetched
+//!   elsewhere.
+//! - `strict`: reject unknown attributes when deserializing responses.
+//! - `test-util`: canned responses in [`test_util`], for testing code built on this crate.
 
-use std::fmt::Write;
-
-use md5::{Digest, Md5};
+use quick_xml::events::Event;
 use serde::de::DeserializeOwned;
 
-use crate::api_types::{TopAlbums, TopArtists, TopTracks};
+/// The version of the Last.fm web services API this crate talks to.
+pub const API_VERSION: &str = "2.0";
 
 pub mod api_types;
-
-macro_rules! request_builder {
-    (
-        $(#[doc = $doc:literal])?
-        struct $name:ident<$life:lifetime> {
-        method: $method:expr,
-        required: { $(
-            $required:ident: $rtyp:ty
-        ),* $(,)? } $(,)?
-        optional: { $(
-            $( #[doc = $odoc:literal] )?
-            $optional:ident: $otyp:ty
-            ),* $(,)? } $(,)?
-        }
-        => $api:ty
-        => $ret:ty ) => {
-        $(#[doc = $doc])?
-        pub struct $name<$life> {
-            client: &$life Client,
-            $($required: $rtyp,)*
-            $($optional: Option<$otyp>,)*
-        }
-
-        impl<$life> $name<$life> {
-            fn new(client: &$life Client,  $($required: $rtyp),*) -> Self {
-                Self {
-                    client,
-                    $($required,)*
-                    $($optional: None),*
-                }
-            }
-
-            $(
-                $( #[doc = $odoc] )?
-                pub fn $optional(mut self, $optional: $otyp ) -> Self {
-                    self.$optional = Some($optional);
-                    self
-                }
-            )*
-
-            pub async fn send(self) -> Result<$ret, Error> {
-                #[allow(unused_mut)]
-                let mut args: Vec<(&str, String)> = vec![
-                    $((stringify!($required), self.$required.to_string() ) )*
-                ];
-
-                $(
-                    if let Some($optional) = self.$optional {
-                        args.push((stringify!($optional), $optional.to_string()));
-                    }
-                )*
-
-                self.client
-                    .make_request::<$api>(
-                        $method,
-                        args.iter().map(|x| (x.0, x.1.as_str())),
-                    )
-                    .await?
-                    .into_result()
-                    .map_err(Error::Api)
-            }
-
-        }
-    };
+#[cfg(feature = "client")]
+pub mod cache;
+#[cfg(feature = "client")]
+mod client;
+mod json;
+pub mod methods;
+#[cfg(feature = "client")]
+pub mod retry;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+
+#[cfg(feature = "client")]
+pub use client::*;
+pub use json::decode_json;
+
+/// The Last.fm API methods supported by this crate, e.g. `user.getTopAlbums`.
+pub fn methods() -> &'static [&'static str] {
+    methods::ALL
 }
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
+    #[cfg(feature = "client")]
     #[error("HTTP {0}")]
     Http(reqwest::Error),
-    #[error("Decoding {0}")]
-    Decoding(quick_xml::DeError),
+    /// The body could not be parsed. Carries the start of the body, to tell what was sent
+    /// instead.
+    #[error("Decoding {0} in {1:?}")]
+    Decoding(quick_xml::DeError, String),
     #[error("Lastfm {0}")]
     Api(api_types::Error),
+    #[error("Unexpected response status {0:?}")]
+    UnexpectedStatus(String),
+    #[error("Session key is no longer valid, authenticate again")]
+    InvalidSession,
+    #[error("Missing Last.fm {0}")]
+    MissingCredential(&'static str),
+    /// Last.fm answered with its maintenance page instead of XML.
+    #[error("Last.fm is under maintenance")]
+    Maintenance,
 }
 
-pub struct Client {
-    shared_secret: String,
-    api_key: String,
-    client: reqwest::Client,
-    base_url: String,
-}
-
-impl std::fmt::Debug for Client {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Client")
-            .field("shared_secret", &"<shared_secret>")
-            .field("api_key", &"<api_key>")
-            .field("client", &self.client)
-            .field("base_url", &self.base_url)
-            .finish()
-    }
-}
-
-impl Client {
-    pub fn new(shared_secret: String, api_key: String) -> Self {
-        Self {
-            shared_secret,
-            api_key,
-            client: reqwest::Client::new(),
-            base_url: "https://ws.audioscrobbler.com/2.0/".into(),
+/// Deserialize a response body, telling responses with an unknown `<lfm status>` and the
+/// maintenance page apart from otherwise malformed ones.
+///
+/// The body is an entire `<lfm>` document, so `T` is one of the `*Response` types in
+/// [`api_types`]. It may start with a byte order mark and an XML declaration.
+pub fn decode<T>(body: &str) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let body = strip_bom(body);
+    quick_xml::de::from_str(body).map_err(|e| {
+        if is_maintenance_page(body) {
+            return Error::Maintenance;
         }
-    }
 
-    async fn make_request<'a, T>(
-        &self,
-        method: &str,
-        args: impl IntoIterator<Item = (&'a str, &'a str)>,
-    ) -> Result<T, Error>
-    where
-        T: DeserializeOwned,
-    {
-        let mut args: Vec<_> = args.into_iter().collect();
-        args.push(("method", method));
-        args.push(("api_key", &self.api_key));
-        args.sort_unstable();
-        let mut hasher = Md5::new();
-        for (k, v) in &args {
-            hasher.update(k);
-            hasher.update(v);
-        }
-        hasher.update(&self.shared_secret);
-        let sign = hasher.finalize();
-        let mut signature = String::with_capacity(2 * sign.len());
-        for ch in sign {
-            let _ = write!(&mut signature, "{ch:02x}");
+        #[derive(serde::Deserialize)]
+        struct Status {
+            #[serde(rename = "@status")]
+            status: Option<String>,
         }
 
-        use Error::{Decoding, Http};
-
-        let resp = self
-            .client
-            .get(&self.base_url)
-            .query(&args)
-            .query(&[("api_sig", signature)])
-            .send()
-            .await
-            .map_err(Http)?
-            .text()
-            .await
-            .map_err(Http)?;
-
-        quick_xml::de::from_str(&resp).map_err(Decoding)
-    }
-
-    pub async fn authenticate(&self, token: &str) -> Result<api_types::Session, Error> {
-        self.make_request::<api_types::AuthGetSessionResponse>(
-            "auth.getSession",
-            [("token", token)],
-        )
-        .await?
-        .0
-        .into_result()
-        .map_err(Error::Api)
-    }
-
-    pub fn top_tracks<'a>(&'a self, user: &'a str) -> GetTopTracks<'a> {
-        GetTopTracks::new(self, user)
-    }
-
-    pub fn top_albums<'a>(&'a self, user: &'a str) -> GetTopAlbums<'a> {
-        GetTopAlbums::new(self, user)
-    }
-
-    pub fn top_artists<'a>(&'a self, user: &'a str) -> GetTopArtists<'a> {
-        GetTopArtists::new(self, user)
-    }
-
-    pub fn top_artists_charts<'a>(&'a self) -> GetTopArtistsCharts<'a> {
-        GetTopArtistsCharts::new(self)
-    }
-
-    pub fn top_tags_charts<'a>(&'a self) -> GetTopTagsCharts<'a> {
-        GetTopTagsCharts::new(self)
-    }
-
-    pub fn top_tracks_charts<'a>(&'a self) -> GetTopTracksCharts<'a> {
-        GetTopTracksCharts::new(self)
-    }
-}
-
-request_builder! {
-    struct GetTopAlbums<'a> {
-        method: "user.getTopAlbums",
-        required: {
-            user: &'a str,
+        // other documents, like the error page of a proxy, have no status to speak of
+        if root_element(body).as_deref() == Some(b"lfm".as_slice())
+            && let Ok(Status { status }) = quick_xml::de::from_str::<Status>(body)
+            && !matches!(status.as_deref(), Some("ok" | "failed"))
+        {
+            return Error::UnexpectedStatus(status.unwrap_or_default());
         }
-        optional: {
-            /// The time period over which to retrieve top artists for.
-            period: api_types::Period,
-            /// The page number to fetch. Defaults to first page.
-            page: usize,
-            /// The number of results to fetch per page. Defaults to 50.
-            limit: usize,
-        }
-    }
-    => api_types::GetTopAlbumsResponse
-    => TopAlbums
+        Error::Decoding(e, excerpt(body, BODY_EXCERPT_LEN).to_owned())
+    })
 }
 
-request_builder! {
-    struct GetTopTracks<'a> {
-        method: "user.getTopTracks",
-        required: {
-            user: &'a str,
-        }
-        optional: {
-            /// The time period over which to retrieve top tracks for.
-            period: api_types::Period,
-            /// The page number to fetch. Defaults to first page.
-            page: usize,
-            /// The number of results to fetch per page. Defaults to 50.
-            limit: usize,
+/// The name of the first element of `body`, if it has one.
+fn root_element(body: &str) -> Option<Vec<u8>> {
+    let mut reader = quick_xml::Reader::from_str(body);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e) | Event::Empty(e)) => return Some(e.local_name().as_ref().to_vec()),
+            Ok(Event::Eof) | Err(_) => return None,
+            Ok(_) => {}
         }
     }
-    => api_types::GetTopTracksResponse
-    => TopTracks
 }
 
-request_builder! {
-    struct GetTopArtists<'a> {
-        method: "user.getTopArtists",
-        required: {
-            user: &'a str,
-        }
-        optional: {
-            /// The time period over which to retrieve top artists for.
-            period: api_types::Period,
-            /// The page number to fetch. Defaults to first page.
-            page: usize,
-            /// The number of results to fetch per page. Defaults to 50.
-            limit: usize,
-        }
-    }
-    => api_types::GetTopArtistsResponse
-    => TopArtists
+/// `body` without the UTF-8 byte order mark some responses start with, which the XML and JSON
+/// parsers would take for content.
+fn strip_bom(body: &str) -> &str {
+    body.strip_prefix('\u{feff}').unwrap_or(body)
 }
 
-request_builder! {
-    struct GetTopArtistsCharts<'a> {
-        method: "chart.getTopArtists",
-        required: { }
-        optional: {
-            /// The page number to fetch. Defaults to first page.
-            page: usize,
-            /// The number of results to fetch per page. Defaults to 50.
-            limit: usize,
-        }
-    }
-    => api_types::chart::GetTopArtistsResponse
-    => api_types::chart::TopArtists
+/// How much of a body that failed to decode is kept in [`Error::Decoding`], in bytes.
+const BODY_EXCERPT_LEN: usize = 256;
+
+/// The first `max_len` bytes of `body`, fewer if that would split a character.
+fn excerpt(body: &str, max_len: usize) -> &str {
+    &body[..body.floor_char_boundary(max_len)]
 }
 
-request_builder! {
-    struct GetTopTagsCharts<'a> {
-        method: "chart.getTopTags",
-        required: { }
-        optional: {
-            /// The page number to fetch. Defaults to first page.
-            page: usize,
-            /// The number of results to fetch per page. Defaults to 50.
-            limit: usize,
-        }
-    }
-    => api_types::chart::GetTopTagsResponse
-    => api_types::chart::TopTags
+/// Whether `body` is the HTML page Last.fm serves, with a `200 OK`, while it is down for
+/// maintenance.
+fn is_maintenance_page(body: &str) -> bool {
+    let start = body.trim_start().get(..14).unwrap_or_default();
+    let is_html = start.eq_ignore_ascii_case("<!doctype html") || start.starts_with("<html");
+    is_html && body.to_ascii_lowercase().contains("maintenance")
 }
 
-request_builder! {
-    struct GetTopTracksCharts<'a> {
-        method: "chart.getTopTracks",
-        required: { }
-        optional: {
-            /// The page number to fetch. Defaults to first page.
-            page: usize,
-            /// The number of results to fetch per page. Defaults to 50.
-            limit: usize,
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_supported_methods() {
+        let methods = methods();
+        assert!(methods.contains(&"user.getTopAlbums"));
+        assert!(methods.contains(&"chart.getTopTags"));
+    }
+
+    #[test]
+    fn unexpected_status() {
+        let weird = decode::<api_types::AuthGetSessionResponse>(
+            r#"<lfm status="weird"><session><name>RJ</name></session></lfm>"#,
+        );
+        assert!(matches!(weird, Err(Error::UnexpectedStatus(status)) if status == "weird"));
+
+        let missing = decode::<api_types::AuthGetSessionResponse>("<lfm><session/></lfm>");
+        assert!(matches!(missing, Err(Error::UnexpectedStatus(status)) if status.is_empty()));
+
+        let malformed = decode::<api_types::AuthGetSessionResponse>(
+            r#"<lfm status="ok"><session><name>RJ</name></session></lfm>"#,
+        );
+        assert!(
+            matches!(malformed, Err(Error::Decoding(_, body)) if body.starts_with(r#"<lfm status="ok">"#))
+        );
+    }
+
+    #[test]
+    fn byte_order_mark_and_declaration() {
+        let session = decode::<api_types::AuthGetSessionResponse>(
+            "\u{feff}<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <lfm status=\"ok\"><session><name>RJ</name><key>sk</key><subscriber>0</subscriber></session></lfm>",
+        );
+        assert_eq!(
+            session.unwrap().0.into_result().unwrap(),
+            api_types::Session {
+                name: "RJ".into(),
+                key: "sk".into(),
+                subscriber: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn excerpt_keeps_characters_whole() {
+        // "é" takes the bytes 3 and 4
+        let body = "<a>é</a>";
+        assert_eq!(excerpt(body, 3), "<a>");
+        assert_eq!(excerpt(body, 4), "<a>");
+        assert_eq!(excerpt(body, 5), "<a>é");
+        assert_eq!(excerpt(body, 100), body);
+
+        // the 256th byte is the second one of an "é"
+        let long = format!("<lfm status=\"ok\">{}</lfm>", "é".repeat(200));
+        let Err(Error::Decoding(_, kept)) = decode::<api_types::GetTopAlbumsResponse>(&long) else {
+            panic!("expected a decoding error");
+        };
+        assert_eq!(kept.len(), BODY_EXCERPT_LEN - 1);
+        assert!(long.starts_with(&kept));
+    }
+
+    #[test]
+    fn maintenance_page() {
+        let page = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>Last.fm | Down for maintenance</title>
+</head>
+<body>
+  <h1>We'll be back soon</h1>
+  <p>Last.fm is currently down for scheduled maintenance. Please check back shortly.</p>
+</body>
+</html>"#;
+        let res = decode::<api_types::AuthGetSessionResponse>(page);
+        assert!(matches!(res, Err(Error::Maintenance)), "{res:?}");
+
+        let other = decode::<api_types::AuthGetSessionResponse>(
+            "<!DOCTYPE html><html><body>Not found</body></html>",
+        );
+        assert!(
+            matches!(other, Err(Error::Decoding(_, ref body)) if body.starts_with("<!DOCTYPE html>")),
+            "{other:?}"
+        );
+    }
+
+    #[test]
+    fn other_documents_are_not_responses() {
+        let res = decode::<api_types::AuthGetSessionResponse>(
+            "<?xml version=\"1.0\"?><error><code>404</code><message>Not Found</message></error>",
+        );
+        assert!(
+            matches!(res, Err(Error::Decoding(_, ref body)) if body.contains("<error>")),
+            "{res:?}"
+        );
     }
-    => api_types::chart::GetTopTracksResponse
-    => api_types::chart::TopTracks
 }
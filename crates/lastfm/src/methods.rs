@@ -0,0 +1,43 @@
+//! Names of the Last.fm API methods supported by this crate.
+//!
+//! Every request builder refers to one of these constants, so [`ALL`] is the complete list.
+
+macro_rules! methods {
+    ($( $name:ident = $method:literal ),* $(,)?) => {
+        $( pub const $name: &str = $method; )*
+
+        /// Every method supported by this crate.
+        pub const ALL: &[&str] = &[$($name),*];
+    };
+}
+
+methods! {
+    AUTH_GET_SESSION = "auth.getSession",
+    USER_GET_TOP_ALBUMS = "user.getTopAlbums",
+    USER_GET_TOP_ARTISTS = "user.getTopArtists",
+    USER_GET_TOP_TRACKS = "user.getTopTracks",
+    USER_GET_WEEKLY_ALBUM_CHART = "user.getWeeklyAlbumChart",
+    USER_GET_WEEKLY_CHART_LIST = "user.getWeeklyChartList",
+    USER_GET_RECENT_TRACKS = "user.getRecentTracks",
+    USER_GET_FRIENDS = "user.getFriends",
+    USER_GET_INFO = "user.getInfo",
+    USER_GET_LOVED_TRACKS = "user.getLovedTracks",
+    USER_GET_PERSONAL_TAGS = "user.getPersonalTags",
+    TAG_GET_INFO = "tag.getInfo",
+    TAG_GET_TOP_ALBUMS = "tag.getTopAlbums",
+    TAG_GET_TOP_ARTISTS = "tag.getTopArtists",
+    TRACK_GET_SIMILAR = "track.getSimilar",
+    TRACK_GET_INFO = "track.getInfo",
+    TRACK_SEARCH = "track.search",
+    ALBUM_GET_INFO = "album.getInfo",
+    ALBUM_SEARCH = "album.search",
+    ARTIST_GET_CORRECTION = "artist.getCorrection",
+    ARTIST_GET_INFO = "artist.getInfo",
+    ARTIST_GET_SIMILAR = "artist.getSimilar",
+    ARTIST_SEARCH = "artist.search",
+    CHART_GET_TOP_ARTISTS = "chart.getTopArtists",
+    CHART_GET_TOP_TAGS = "chart.getTopTags",
+    CHART_GET_TOP_TRACKS = "chart.getTopTracks",
+    GEO_GET_TOP_ARTISTS = "geo.getTopArtists",
+    GEO_GET_TOP_TRACKS = "geo.getTopTracks",
+}
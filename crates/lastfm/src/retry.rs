@@ -0,0 +1,103 @@
+//! Backoff schedule for retrying transient Last.fm failures.
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+type JitterSource = dyn FnMut() -> f64 + Send;
+
+/// Exponential backoff with jitter.
+///
+/// The delay before retry `n` (starting at 0) is `base_delay * 2^n` plus a jitter of up to half
+/// of that again. The jitter is drawn from a source yielding values in `[0, 1)`, which defaults
+/// to real randomness but can be pinned for tests with [`Backoff::with_seed`] or
+/// [`Backoff::with_jitter`].
+#[derive(Clone)]
+pub struct Backoff {
+    base_delay: Duration,
+    jitter: Arc<Mutex<JitterSource>>,
+}
+
+impl fmt::Debug for Backoff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Backoff")
+            .field("base_delay", &self.base_delay)
+            .field("jitter", &"<jitter>")
+            .finish()
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(500))
+    }
+}
+
+impl Backoff {
+    pub fn new(base_delay: Duration) -> Self {
+        Self {
+            base_delay,
+            jitter: Arc::new(Mutex::new(|| rand::rng().random::<f64>())),
+        }
+    }
+
+    /// Draw the jitter from an RNG seeded with `seed`, making the delay sequence reproducible.
+    pub fn with_seed(self, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        self.with_jitter(move || rng.random::<f64>())
+    }
+
+    /// Draw the jitter from `jitter`, which should yield values in `[0, 1)`.
+    pub fn with_jitter(mut self, jitter: impl FnMut() -> f64 + Send + 'static) -> Self {
+        self.jitter = Arc::new(Mutex::new(jitter));
+        self
+    }
+
+    pub fn base_delay(&self) -> Duration {
+        self.base_delay
+    }
+
+    /// The delay to wait before retry number `attempt`.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exp = self.base_delay.saturating_mul(2u32.saturating_pow(attempt));
+        let factor = (self.jitter.lock().unwrap())().clamp(0.0, 1.0);
+        exp.saturating_add(exp.mul_f64(factor / 2.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    fn delays(backoff: &Backoff, n: u32) -> Vec<Duration> {
+        (0..n).map(|attempt| backoff.delay(attempt)).collect()
+    }
+
+    #[test]
+    fn pinned_jitter_gives_exact_delays() {
+        let backoff = Backoff::new(Duration::from_millis(100)).with_jitter(|| 0.5);
+        assert_eq!(
+            delays(&backoff, 4),
+            [125, 250, 500, 1000].map(Duration::from_millis)
+        );
+    }
+
+    #[test]
+    fn seeded_jitter_is_deterministic() {
+        let a = Backoff::new(Duration::from_millis(100)).with_seed(42);
+        let b = Backoff::new(Duration::from_millis(100)).with_seed(42);
+        let a = delays(&a, 5);
+        assert_eq!(a, delays(&b, 5));
+
+        for (attempt, delay) in a.into_iter().enumerate() {
+            let exp = Duration::from_millis(100) * 2u32.pow(attempt as u32);
+            assert!(exp <= delay && delay <= exp + exp / 2, "{delay:?}");
+        }
+    }
+}
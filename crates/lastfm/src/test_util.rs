@@ -0,0 +1,48 @@
+//! Canned responses for tests, in this crate and in its users.
+
+/// `user.getTopAlbums` responses for `RJ`, one per entry of `pages`, each listing Haken albums by
+/// the given names.
+///
+/// The pagination attributes and ranks agree with `pages`: `perPage` is the longest page,
+/// `totalPages` the number of pages and `total` the number of albums across all of them.
+pub fn top_albums_pages(pages: &[&[&str]]) -> Vec<String> {
+    let per_page = pages.iter().map(|names| names.len()).max().unwrap_or(0);
+    let total: usize = pages.iter().map(|names| names.len()).sum();
+    let total_pages = pages.len();
+    pages
+        .iter()
+        .enumerate()
+        .map(|(i, names)| {
+            let page = i + 1;
+            let albums: String = names
+                .iter()
+                .enumerate()
+                .map(|(j, name)| {
+                    let rank = i * per_page + j + 1;
+                    format!(
+                        r#"<album rank="{rank}">
+    <name>{name}</name>
+    <playcount>1</playcount>
+    <mbid></mbid>
+    <url>https://www.last.fm/music/Haken</url>
+    <artist>
+      <name>Haken</name>
+      <mbid></mbid>
+      <url>https://www.last.fm/music/Haken</url>
+    </artist>
+    <image size="small"></image>
+  </album>"#
+                    )
+                })
+                .collect();
+            format!(
+                r#"<lfm status="ok"><topalbums user="RJ" type="overall" page="{page}" perPage="{per_page}" totalPages="{total_pages}" total="{total}">{albums}</topalbums></lfm>"#
+            )
+        })
+        .collect()
+}
+
+/// A `user.getTopAlbums` response that fits on a single page.
+pub fn top_albums(names: &[&str]) -> String {
+    top_albums_pages(&[names]).remove(0)
+}
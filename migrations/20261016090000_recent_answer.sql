@@ -0,0 +1,10 @@
+-- Answers handed out per user, so that newgame can avoid repeating them
+create table if not exists recent_answer (
+    id INTEGER
+    , lastfm_name TEXT NOT NULL
+    , answer_key TEXT NOT NULL
+    , created_at INTEGER NOT NULL
+    , primary key (id)
+);
+
+create index if not exists recent_answer_by_user on recent_answer (lastfm_name, id);
@@ -0,0 +1,6 @@
+-- The newgame settings each user chose last, used when they leave them out
+create table if not exists user_settings (
+    lastfm_name TEXT NOT NULL
+    , periods TEXT NOT NULL
+    , primary key (lastfm_name)
+);
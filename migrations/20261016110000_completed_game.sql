@@ -0,0 +1,12 @@
+-- Finished games by answer, for stats on which answers are hard in practice
+create table if not exists completed_game (
+    id INTEGER
+    , answer_key TEXT NOT NULL
+    , answer TEXT NOT NULL
+    , solved INTEGER NOT NULL
+    , guesses INTEGER NOT NULL
+    , completed_at INTEGER NOT NULL
+    , primary key (id)
+);
+
+create index if not exists completed_game_by_answer on completed_game (answer_key);
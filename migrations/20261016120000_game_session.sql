@@ -0,0 +1,9 @@
+-- The games in progress, when SESSION_BACKEND is sqlite
+create table if not exists game_session (
+    id TEXT NOT NULL
+    -- a `StoredSession` as JSON
+    , state TEXT NOT NULL
+    -- bumped on every change, so that two guesses graded at once are noticed
+    , revision INTEGER NOT NULL DEFAULT 0
+    , primary key (id)
+);
@@ -0,0 +1,11 @@
+-- Games created per Idempotency-Key, so that retried requests get the same game
+create table if not exists idempotent_game (
+    endpoint TEXT NOT NULL
+    , lastfm_name TEXT NOT NULL
+    , key TEXT NOT NULL
+    -- a `NewGameResult` as JSON
+    , game TEXT NOT NULL
+    -- unix seconds
+    , created_at INTEGER NOT NULL
+    , primary key (endpoint, lastfm_name, key)
+);
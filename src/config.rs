@@ -0,0 +1,278 @@
+use std::time::Duration;
+
+use figment::{
+    Figment,
+    providers::{Env, Format, Toml},
+};
+use hmac::{Hmac, Mac};
+use lastfm::api_types::Period;
+use serde::Deserialize;
+use sha2::Sha512;
+use time::{Date, macros::date};
+
+use crate::{game::SeedSource, sessions::SessionBackend};
+
+/// The file read for settings not given in the environment.
+const CONFIG_FILE: &str = "bandordle.toml";
+
+pub struct Config {
+    pub lastfm_apikey: String,
+    pub lastfm_shared_secret: String,
+    pub auth_callback_url: String,
+    pub jwt_key: Hmac<Sha512>,
+    pub database_url: String,
+    /// Upper bound on how long a handler waits for Last.fm.
+    pub lastfm_timeout: Duration,
+    /// Keep guesses that were rejected (e.g. for having the wrong length) in a game's history.
+    pub record_rejected_guesses: bool,
+    /// Longest album or artist name that can become a puzzle.
+    pub max_answer_len: Option<usize>,
+    /// How many of a user's previous answers newgame avoids repeating. 0 allows repeats.
+    pub avoid_recent_answers: usize,
+    /// Bearer token granting access to the admin endpoints. They are disabled when unset.
+    pub admin_token: Option<String>,
+    /// Where album games draw their answer from, tried in order until one has a candidate.
+    pub seed_sources: Vec<SeedSource>,
+    /// The day of puzzle #1. Puzzles are numbered by the days since, like "Bandordle #123".
+    pub puzzle_epoch: Date,
+    /// The period new games draw from unless the player asks for, or last used, another one.
+    pub default_period: Period,
+    /// Keep the settings of each player's last game as defaults for their next one.
+    pub remember_settings: bool,
+    /// Look up the release year of album answers on Last.fm to show it in the reveal.
+    pub reveal_release_year: bool,
+    /// Where the games in progress are kept.
+    pub session_backend: SessionBackend,
+    /// Users whose top albums are fetched at startup, so that their first game is drawn quickly.
+    pub warmup_users: Vec<String>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("missing configuration: {}", .0.join(", "))]
+    Missing(Vec<&'static str>),
+    #[error("invalid configuration: {0}")]
+    Invalid(#[from] Box<figment::Error>),
+}
+
+/// The configuration as written by the user, keyed by the lowercased environment variable names.
+#[derive(Deserialize)]
+struct RawConfig {
+    lastfm_apikey: Option<String>,
+    lastfm_shared_secret: Option<String>,
+    auth_callback_url: Option<String>,
+    jwt_key: Option<String>,
+    database_url: Option<String>,
+    #[serde(default = "default_lastfm_timeout_secs")]
+    lastfm_timeout_secs: u64,
+    #[serde(default)]
+    record_rejected_guesses: bool,
+    max_answer_len: Option<usize>,
+    #[serde(default)]
+    avoid_recent_answers: usize,
+    admin_token: Option<String>,
+    #[serde(default = "default_seed_sources")]
+    seed_sources: Vec<SeedSource>,
+    #[serde(default = "default_puzzle_epoch")]
+    puzzle_epoch: Date,
+    #[serde(default = "default_period")]
+    default_period: Period,
+    #[serde(default = "default_remember_settings")]
+    remember_settings: bool,
+    #[serde(default = "default_reveal_release_year")]
+    reveal_release_year: bool,
+    #[serde(default)]
+    session_backend: SessionBackend,
+    #[serde(default)]
+    warmup_users: Vec<String>,
+}
+
+fn default_lastfm_timeout_secs() -> u64 {
+    10
+}
+
+fn default_puzzle_epoch() -> Date {
+    date!(2025 - 01 - 01)
+}
+
+fn default_period() -> Period {
+    Period::Overall
+}
+
+fn default_remember_settings() -> bool {
+    true
+}
+
+fn default_reveal_release_year() -> bool {
+    true
+}
+
+fn default_seed_sources() -> Vec<SeedSource> {
+    vec![
+        SeedSource::TopAlbums,
+        SeedSource::RecentTracks,
+        SeedSource::Charts,
+    ]
+}
+
+impl Config {
+    /// Load the configuration from [`CONFIG_FILE`] (if present), overridden by environment
+    /// variables.
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::from_figment(Self::figment(CONFIG_FILE))
+    }
+
+    fn figment(file: &str) -> Figment {
+        Figment::new()
+            .merge(Toml::file(file))
+            .merge(Env::raw().only(&[
+                "LASTFM_APIKEY",
+                "LASTFM_SHARED_SECRET",
+                "AUTH_CALLBACK_URL",
+                "JWT_KEY",
+                "DATABASE_URL",
+                "LASTFM_TIMEOUT_SECS",
+                "RECORD_REJECTED_GUESSES",
+                "MAX_ANSWER_LEN",
+                "AVOID_RECENT_ANSWERS",
+                "ADMIN_TOKEN",
+                "SEED_SOURCES",
+                "PUZZLE_EPOCH",
+                "DEFAULT_PERIOD",
+                "REMEMBER_SETTINGS",
+                "REVEAL_RELEASE_YEAR",
+                "SESSION_BACKEND",
+                "WARMUP_USERS",
+            ]))
+    }
+
+    /// Extract the configuration, reporting every missing setting at once.
+    fn from_figment(figment: Figment) -> Result<Self, ConfigError> {
+        let raw: RawConfig = figment.extract().map_err(Box::new)?;
+
+        let mut missing = Vec::new();
+        let mut require = |value: Option<String>, name: &'static str| {
+            if value.is_none() {
+                missing.push(name);
+            }
+            value.unwrap_or_default()
+        };
+        let lastfm_apikey = require(raw.lastfm_apikey, "LASTFM_APIKEY");
+        let lastfm_shared_secret = require(raw.lastfm_shared_secret, "LASTFM_SHARED_SECRET");
+        let auth_callback_url = require(raw.auth_callback_url, "AUTH_CALLBACK_URL");
+        let jwt_key = require(raw.jwt_key, "JWT_KEY");
+        let database_url = require(raw.database_url, "DATABASE_URL");
+        if !missing.is_empty() {
+            return Err(ConfigError::Missing(missing));
+        }
+
+        Ok(Self {
+            lastfm_apikey,
+            lastfm_shared_secret,
+            auth_callback_url,
+            jwt_key: Hmac::new_from_slice(jwt_key.as_bytes()).expect("hmac accepts any key size"),
+            database_url,
+            lastfm_timeout: Duration::from_secs(raw.lastfm_timeout_secs),
+            record_rejected_guesses: raw.record_rejected_guesses,
+            max_answer_len: raw.max_answer_len,
+            avoid_recent_answers: raw.avoid_recent_answers,
+            admin_token: raw.admin_token,
+            seed_sources: raw.seed_sources,
+            puzzle_epoch: raw.puzzle_epoch,
+            default_period: raw.default_period,
+            remember_settings: raw.remember_settings,
+            reveal_release_year: raw.reveal_release_year,
+            session_backend: raw.session_backend,
+            warmup_users: raw.warmup_users,
+        })
+    }
+}
+
+#[cfg(test)]
+// `Jail` closures have to return the (large) `figment::Error`
+#[allow(clippy::result_large_err)]
+mod tests {
+    use super::*;
+
+    use figment::Jail;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn file_with_env_overrides() {
+        Jail::expect_with(|jail| {
+            jail.clear_env();
+            jail.create_file(
+                "bandordle.toml",
+                r#"
+                lastfm_apikey = "file-apikey"
+                lastfm_shared_secret = "file-secret"
+                auth_callback_url = "http://localhost:5173/login"
+                jwt_key = "file-jwt"
+                database_url = "sqlite://bandordle.db"
+                "#,
+            )?;
+            jail.set_env("LASTFM_APIKEY", "env-apikey");
+            jail.set_env("LASTFM_TIMEOUT_SECS", "3");
+            jail.set_env("PUZZLE_EPOCH", "2026-10-16");
+            jail.set_env("DEFAULT_PERIOD", "1month");
+            jail.set_env("SESSION_BACKEND", "sqlite");
+
+            let config = Config::from_figment(Config::figment("bandordle.toml")).unwrap();
+            assert_eq!(config.lastfm_apikey, "env-apikey");
+            assert_eq!(config.lastfm_shared_secret, "file-secret");
+            assert_eq!(config.auth_callback_url, "http://localhost:5173/login");
+            assert_eq!(config.database_url, "sqlite://bandordle.db");
+            assert_eq!(config.lastfm_timeout, Duration::from_secs(3));
+            assert_eq!(config.seed_sources, default_seed_sources());
+            assert_eq!(config.puzzle_epoch, date!(2026 - 10 - 16));
+            assert_eq!(config.default_period, Period::OneMonth);
+            assert!(config.remember_settings);
+            assert!(config.reveal_release_year);
+            assert_eq!(config.session_backend, SessionBackend::Sqlite);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn reports_all_missing_settings() {
+        Jail::expect_with(|jail| {
+            jail.clear_env();
+            jail.set_env("LASTFM_APIKEY", "apikey");
+            jail.set_env("DATABASE_URL", "sqlite://bandordle.db");
+
+            let err = Config::from_figment(Config::figment("bandordle.toml"))
+                .err()
+                .unwrap();
+            assert_eq!(
+                err.to_string(),
+                "missing configuration: LASTFM_SHARED_SECRET, AUTH_CALLBACK_URL, JWT_KEY"
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn seed_sources_from_env() {
+        Jail::expect_with(|jail| {
+            jail.clear_env();
+            jail.set_env("SEED_SOURCES", "[recent-tracks, charts]");
+            let raw: RawConfig = Config::figment("bandordle.toml").extract()?;
+            assert_eq!(
+                raw.seed_sources,
+                [SeedSource::RecentTracks, SeedSource::Charts]
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn rejects_invalid_timeout() {
+        Jail::expect_with(|jail| {
+            jail.clear_env();
+            jail.set_env("LASTFM_TIMEOUT_SECS", "soon");
+            let res = Config::from_figment(Config::figment("bandordle.toml"));
+            assert!(matches!(res, Err(ConfigError::Invalid(_))));
+            Ok(())
+        });
+    }
+}
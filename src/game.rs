@@ -0,0 +1,803 @@
+use hmac::{Hmac, Mac};
+use lastfm::api_types::{Album, Artist, RecentTrack, Track, chart, largest};
+use sha2::Sha512;
+use std::{
+    fmt::Display,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+use unicode_normalization::UnicodeNormalization;
+
+use rand::{
+    Rng, RngCore,
+    distr::{Distribution, weighted::WeightedIndex},
+    rngs::StdRng,
+};
+use serde::{Deserialize, Serialize};
+use time::Date;
+use ts_rs::TS;
+
+use crate::AppError;
+
+#[derive(
+    Debug, PartialEq, Eq, Default, Clone, Copy, Hash, PartialOrd, Ord, Serialize, Deserialize, TS,
+)]
+pub enum Grade {
+    #[default]
+    Incorrect,
+    WrongPlace,
+    Correct,
+}
+
+impl Grade {
+    /// The single-letter code of the grade, as stored in boards.
+    pub fn as_code(self) -> &'static str {
+        match self {
+            Grade::Incorrect => "I",
+            Grade::WrongPlace => "W",
+            Grade::Correct => "C",
+        }
+    }
+}
+
+impl Display for Grade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_code())
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[error("unknown grade {0}")]
+pub struct ParseGradeError(pub String);
+
+/// Accepts the variant names, the codes of [`Grade::as_code`] and the squares of shared boards.
+impl FromStr for Grade {
+    type Err = ParseGradeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Incorrect" | "I" | "⬛" | "⬜" => Grade::Incorrect,
+            "WrongPlace" | "W" | "🟨" => Grade::WrongPlace,
+            "Correct" | "C" | "🟩" => Grade::Correct,
+            _ => return Err(ParseGradeError(s.to_owned())),
+        })
+    }
+}
+
+impl TryFrom<&str> for Grade {
+    type Error = ParseGradeError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub enum GradingError {
+    #[error("Wrong length (expected {0}, have {1})")]
+    WrongLength(usize, usize),
+    #[error("Wrong number of words (expected {0}, have {1})")]
+    WrongNumberOfWords(usize, usize),
+}
+
+/// Grade `guess` letter by letter against the `expected` word of the same length.
+///
+/// Two empty words grade to no letters at all, and an empty word against any other is a
+/// [`GradingError::WrongLength`]. Puzzles never contain empty words, see `crate::start_game`.
+pub fn grade(expected: &str, guess: &str) -> Result<Vec<Grade>, GradingError> {
+    if expected.len() != guess.len() {
+        return Err(GradingError::WrongLength(expected.len(), guess.len()));
+    }
+
+    let mut word: Vec<_> = guess.chars().map(Some).collect();
+    let mut expected: Vec<_> = expected.chars().map(Some).collect();
+
+    let mut ret = vec![Grade::Incorrect; expected.len()];
+
+    for (i, (w, e)) in word.iter_mut().zip(expected.iter_mut()).enumerate() {
+        if w == e {
+            ret[i] = Grade::Correct;
+            *w = None;
+            *e = None;
+        }
+    }
+
+    for (i, w) in word.iter().enumerate() {
+        if w.is_none() {
+            continue;
+        }
+        for e in expected.iter_mut() {
+            if w == e {
+                ret[i] = Grade::WrongPlace;
+                *e = None;
+                break;
+            }
+        }
+    }
+
+    Ok(ret)
+}
+
+/// Relative frequency of `a` to `z` in English text, in percent.
+const LETTER_FREQUENCY: [f64; 26] = [
+    8.2, 1.5, 2.8, 4.3, 12.7, 2.2, 2.0, 6.1, 7.0, 0.15, 0.77, 4.0, 2.4, 6.7, 7.5, 1.9, 0.095, 6.0,
+    6.3, 9.1, 2.8, 0.98, 2.4, 0.15, 2.0, 0.074,
+];
+
+/// A heuristic for how hard the answer made of `words` is to guess; higher is harder.
+///
+/// Every word adds
+/// - half a point per letter, as long words take more guesses to pin down,
+/// - up to a point per letter for how rare it is in English (`e` adds nothing, `z` almost a
+///   full point; digits and apostrophes count as rare), as players try common letters first,
+/// - a point per repeated letter, as a letter's second occurrence only shows once it is
+///   guessed twice,
+///
+/// and every word after the first adds another point for having to solve them side by side.
+pub fn difficulty_score(words: &[String]) -> f64 {
+    let max_frequency = LETTER_FREQUENCY.iter().copied().fold(0.0, f64::max);
+    let rarity = |ch: char| match ch {
+        'a'..='z' => 1.0 - LETTER_FREQUENCY[(ch as u8 - b'a') as usize] / max_frequency,
+        _ => 1.0,
+    };
+
+    let per_word: f64 = words
+        .iter()
+        .map(|word| {
+            let letters: Vec<_> = word.chars().collect();
+            let distinct = letters
+                .iter()
+                .collect::<std::collections::HashSet<_>>()
+                .len();
+            let repeats = letters.len() - distinct;
+            0.5 * letters.len() as f64
+                + letters.iter().copied().map(rarity).sum::<f64>()
+                + repeats as f64
+        })
+        .sum();
+    per_word + words.len().saturating_sub(1) as f64
+}
+
+/// The number of the puzzle of `day`, counting from puzzle #1 on `epoch`.
+pub fn puzzle_number(epoch: Date, day: Date) -> i64 {
+    (day - epoch).whole_days() + 1
+}
+
+/// How many bytes of the HMAC a share checksum keeps, so that it fits in a share text.
+const SHARE_CHECKSUM_LEN: usize = 16;
+
+/// The message a share checksum signs: the answer, then each guess on its own line.
+fn share_message(answer: &str, grades: &[Vec<Vec<Grade>>]) -> Vec<u8> {
+    // prefixed, so that the key can be shared with the session tokens
+    let mut message = format!("share\n{answer}");
+    for guess in grades {
+        message.push('\n');
+        for (i, word) in guess.iter().enumerate() {
+            if i > 0 {
+                message.push(' ');
+            }
+            message.extend(word.iter().map(|grade| grade.as_code()));
+        }
+    }
+    message.into_bytes()
+}
+
+/// Hex checksum proving that the board `grades` was played to `answer` on this server.
+pub fn share_checksum(key: &Hmac<Sha512>, answer: &str, grades: &[Vec<Vec<Grade>>]) -> String {
+    let mut mac = key.clone();
+    mac.update(&share_message(answer, grades));
+    mac.finalize().into_bytes()[..SHARE_CHECKSUM_LEN]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Whether `checksum` is the [`share_checksum`] of the board.
+pub fn share_checksum_matches(
+    key: &Hmac<Sha512>,
+    answer: &str,
+    grades: &[Vec<Vec<Grade>>],
+    checksum: &str,
+) -> bool {
+    if checksum.len() != 2 * SHARE_CHECKSUM_LEN || !checksum.bytes().all(|c| c.is_ascii_hexdigit())
+    {
+        return false;
+    }
+    let bytes: Vec<u8> = (0..checksum.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&checksum[i..i + 2], 16).expect("checked to be hex"))
+        .collect();
+    let mut mac = key.clone();
+    mac.update(&share_message(answer, grades));
+    // compares in constant time
+    mac.verify_truncated_left(&bytes).is_ok()
+}
+
+/// How album and artist names are turned into the words of a puzzle.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Sanitize {
+    /// Keep apostrophes as part of the words, so "Caligula's" stays "caligula's" instead of
+    /// becoming "caligulas".
+    pub keep_apostrophes: bool,
+}
+
+impl Sanitize {
+    /// Split `name` into lowercase words, dropping every character that cannot be guessed.
+    /// Accented letters lose their accents, so "Café" becomes "cafe".
+    pub fn words(self, name: &str) -> Vec<String> {
+        let name: String = name
+            .nfd()
+            .filter_map(|ch| match ch {
+                'a'..='z' | '0'..='9' => Some(ch),
+                'A'..='Z' => Some(ch.to_ascii_lowercase()),
+                '\'' | '’' if self.keep_apostrophes => Some('\''),
+                ch if ch.is_whitespace() => Some(ch),
+                _ => None,
+            })
+            .collect();
+        name.split_whitespace().map(ToOwned::to_owned).collect()
+    }
+
+    /// Other titles `name` is commonly known by: without its bracketed parts like
+    /// "(Deluxe Edition)", and without a suffix like " - Remastered 2011".
+    pub fn alternate_titles(self, name: &str) -> Vec<Vec<String>> {
+        let mut depth = 0usize;
+        let unbracketed: String = name
+            .chars()
+            .filter(|&ch| match ch {
+                '(' | '[' => {
+                    depth += 1;
+                    false
+                }
+                ')' | ']' => {
+                    depth = depth.saturating_sub(1);
+                    false
+                }
+                _ => depth == 0,
+            })
+            .collect();
+        let unsuffixed = name.split_once(" - ").map(|(title, _)| title);
+
+        let full = self.words(name);
+        let mut alternates = Vec::new();
+        for title in [Some(unbracketed.as_str()), unsuffixed]
+            .into_iter()
+            .flatten()
+        {
+            let words = self.words(title);
+            if !words.is_empty() && words != full && !alternates.contains(&words) {
+                alternates.push(words);
+            }
+        }
+        alternates
+    }
+}
+
+/// How the answer is drawn from the candidates.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Selection {
+    /// Every candidate is equally likely.
+    #[default]
+    Uniform,
+    /// The probability of a candidate is proportional to its play count.
+    Popular,
+    /// The probability of a candidate is inversely proportional to its play count.
+    DeepCuts,
+}
+
+/// Where the answer pool of a game comes from. Sources are tried in the configured order until
+/// one yields an answer, so that players with empty libraries still get a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export)]
+pub enum SeedSource {
+    /// The user's favorite albums of the requested periods.
+    TopAlbums,
+    /// The tracks the user scrobbled last.
+    RecentTracks,
+    /// The global top tracks.
+    Charts,
+}
+
+impl Selection {
+    fn weight(self, playcount: i64) -> f64 {
+        // a play count of 0 would either never be picked or make every other weight vanish
+        let playcount = playcount.max(1) as f64;
+        match self {
+            Selection::Uniform => 1.0,
+            Selection::Popular => playcount,
+            Selection::DeepCuts => playcount.recip(),
+        }
+    }
+}
+
+/// The RNG answers are drawn with. This is the thread RNG unless seeded, which makes the draws
+/// reproducible.
+#[derive(Clone, Default)]
+pub struct DrawRng(Option<Arc<Mutex<StdRng>>>);
+
+impl DrawRng {
+    #[cfg(test)]
+    pub fn seeded(seed: u64) -> Self {
+        use rand::SeedableRng;
+        Self(Some(Arc::new(Mutex::new(StdRng::seed_from_u64(seed)))))
+    }
+
+    pub fn with<T>(&self, f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+        match &self.0 {
+            Some(rng) => f(&mut *rng.lock().unwrap()),
+            None => f(&mut rand::rng()),
+        }
+    }
+}
+
+/// Something the answer can be drawn from.
+pub trait Candidate {
+    fn name(&self) -> &str;
+    fn playcount(&self) -> i64;
+    /// Identifies the candidate across draws, e.g. to avoid repeating recent answers.
+    fn key(&self) -> String;
+}
+
+macro_rules! impl_candidate {
+    ($($ty:ty => |$this:ident| $key:expr),* $(,)?) => {
+        $(impl Candidate for $ty {
+            fn name(&self) -> &str {
+                &self.name
+            }
+
+            fn playcount(&self) -> i64 {
+                self.playcount
+            }
+
+            fn key(&self) -> String {
+                let $this = self;
+                $key
+            }
+        })*
+    };
+}
+
+impl_candidate! {
+    Album => |album| format!("album/{}/{}", album.artist.name, album.name),
+    Artist => |artist| format!("artist/{}", artist.name),
+    Track => |track| format!("track/{}/{}", track.artist.name, track.name),
+    chart::Track => |track| format!("track/{}/{}", track.artist.name, track.name),
+}
+
+/// Scrobbles carry no play count, so every recent track is equally likely.
+impl Candidate for RecentTrack {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn playcount(&self) -> i64 {
+        1
+    }
+
+    fn key(&self) -> String {
+        format!("track/{}/{}", self.artist.name, self.name)
+    }
+}
+
+/// The entity a game's answer was drawn from, kept for the reveal at the end.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Seed {
+    Album(Album),
+    Track(Track),
+    Artist(Artist),
+    RecentTrack(RecentTrack),
+    ChartTrack(chart::Track),
+}
+
+impl Seed {
+    pub fn name(&self) -> &str {
+        match self {
+            Seed::Album(album) => &album.name,
+            Seed::Track(track) => &track.name,
+            Seed::Artist(artist) => &artist.name,
+            Seed::RecentTrack(track) => &track.name,
+            Seed::ChartTrack(track) => &track.name,
+        }
+    }
+
+    /// The [`Candidate::key`] of the entity.
+    pub fn key(&self) -> String {
+        match self {
+            Seed::Album(album) => album.key(),
+            Seed::Track(track) => track.key(),
+            Seed::Artist(artist) => artist.key(),
+            Seed::RecentTrack(track) => track.key(),
+            Seed::ChartTrack(track) => track.key(),
+        }
+    }
+
+    /// URL of the largest artwork of the entity.
+    pub fn image(&self) -> Option<&str> {
+        let images = match self {
+            Seed::Album(album) => &album.images,
+            Seed::Track(track) => &track.images,
+            Seed::Artist(artist) => &artist.images,
+            Seed::RecentTrack(track) => &track.images,
+            Seed::ChartTrack(track) => &track.images,
+        };
+        largest(images).map(|image| image.url.as_str())
+    }
+}
+
+impl From<Album> for Seed {
+    fn from(album: Album) -> Self {
+        Seed::Album(album)
+    }
+}
+
+impl From<Track> for Seed {
+    fn from(track: Track) -> Self {
+        Seed::Track(track)
+    }
+}
+
+impl From<Artist> for Seed {
+    fn from(artist: Artist) -> Self {
+        Seed::Artist(artist)
+    }
+}
+
+impl From<RecentTrack> for Seed {
+    fn from(track: RecentTrack) -> Self {
+        Seed::RecentTrack(track)
+    }
+}
+
+impl From<chart::Track> for Seed {
+    fn from(track: chart::Track) -> Self {
+        Seed::ChartTrack(track)
+    }
+}
+
+/// Filter rejecting candidates whose name is longer than `max` characters. `None` allows any
+/// length.
+pub fn max_len<T: Candidate>(max: Option<usize>) -> impl Fn(&T) -> bool {
+    move |candidate| max.is_none_or(|max| candidate.name().chars().count() <= max)
+}
+
+/// Filter rejecting candidates whose name has fewer than `min` words once sanitized. `None` allows
+/// any number.
+pub fn min_words<T: Candidate>(min: Option<usize>, sanitize: Sanitize) -> impl Fn(&T) -> bool {
+    move |candidate| min.is_none_or(|min| sanitize.words(candidate.name()).len() >= min)
+}
+
+/// Draw the answer from the `candidates` accepted by `filter`, returning the picked candidate
+/// along with the words of its name and their lengths.
+pub fn pick_word<T: Candidate>(
+    candidates: impl IntoIterator<Item = T>,
+    filter: impl Fn(&T) -> bool,
+    sanitize: Sanitize,
+    selection: Selection,
+    rng: &mut impl Rng,
+) -> Result<(T, Vec<String>), AppError> {
+    let mut candidates: Vec<_> = candidates
+        .into_iter()
+        .filter(|candidate| {
+            candidate
+                .name()
+                .chars()
+                .any(|ch| matches!(ch, 'a'..='z' | 'A'..='Z' | '0'..='9'))
+        })
+        .filter(filter)
+        .collect();
+    let weights = WeightedIndex::new(
+        candidates
+            .iter()
+            .map(|candidate| selection.weight(candidate.playcount())),
+    )
+    .map_err(|_| AppError::NoAlbums)?;
+    let picked = candidates.swap_remove(weights.sample(rng));
+    let words = sanitize.words(picked.name());
+    Ok((picked, words))
+}
+
+/// The length of each of `words`, as the board shows them before the first guess.
+pub fn word_lengths(words: &[String]) -> Vec<usize> {
+    words.iter().map(|x| x.len()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use lastfm::api_types::{Image, ImageSize, ShortArtist};
+    use pretty_assertions::assert_eq;
+    use rand::SeedableRng;
+
+    impl Candidate for (&str, i64) {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn playcount(&self) -> i64 {
+            self.1
+        }
+
+        fn key(&self) -> String {
+            self.0.to_owned()
+        }
+    }
+
+    fn pick_one(name: &str, sanitize: Sanitize) -> (Vec<String>, Vec<usize>) {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (_, words) = pick_word(
+            [(name, 1)],
+            |_| true,
+            sanitize,
+            Selection::Uniform,
+            &mut rng,
+        )
+        .unwrap();
+        let len = word_lengths(&words);
+        (words, len)
+    }
+
+    /// How often the album played 1000 times is picked over one played 10 times.
+    fn popular_picks(selection: Selection) -> usize {
+        let mut rng = StdRng::seed_from_u64(42);
+        let candidates = [("Popular", 1000), ("Obscure", 10)];
+        (0..1000)
+            .filter(|_| {
+                let (picked, _) = pick_word(
+                    candidates,
+                    |_| true,
+                    Sanitize::default(),
+                    selection,
+                    &mut rng,
+                )
+                .unwrap();
+                picked.0 == "Popular"
+            })
+            .count()
+    }
+
+    #[test]
+    fn weighted_by_playcount() {
+        let popular = popular_picks(Selection::Popular);
+        assert!(popular > 950, "{popular}");
+        let deep_cuts = popular_picks(Selection::DeepCuts);
+        assert!(deep_cuts < 50, "{deep_cuts}");
+        let uniform = popular_picks(Selection::Uniform);
+        assert!((400..600).contains(&uniform), "{uniform}");
+    }
+
+    #[test]
+    fn no_candidates() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let res = pick_word(
+            [("???", 1)],
+            |_| true,
+            Sanitize::default(),
+            Selection::Popular,
+            &mut rng,
+        );
+        assert!(matches!(res, Err(AppError::NoAlbums)));
+    }
+
+    #[test]
+    fn apostrophes_dropped_by_default() {
+        let (words, len) = pick_one("Caligula's Horse", Sanitize::default());
+        assert_eq!(words, ["caligulas", "horse"]);
+        assert_eq!(len, [9, 5]);
+    }
+
+    fn score(words: &[&str]) -> f64 {
+        let words: Vec<_> = words.iter().map(|&word| word.to_owned()).collect();
+        difficulty_score(&words)
+    }
+
+    #[test]
+    fn difficulty_ordering() {
+        assert_eq!(score(&[]), 0.0);
+        assert!(score(&["rose"]) < score(&["quixotic"]));
+        assert!(
+            score(&["tone"]) < score(&["jazz"]),
+            "rare and repeated letters"
+        );
+        assert!(score(&["toner"]) < score(&["to", "ner"]), "more words");
+        assert!(score(&["stone"]) < score(&["stones"]), "longer");
+    }
+
+    #[test]
+    fn grade_empty_words() {
+        assert_eq!(grade("", ""), Ok(Vec::new()));
+        assert_eq!(grade("", "x"), Err(GradingError::WrongLength(0, 1)));
+        assert_eq!(grade("x", ""), Err(GradingError::WrongLength(1, 0)));
+    }
+
+    #[test]
+    fn alternate_titles_drop_edition_suffixes() {
+        let sanitize = Sanitize::default();
+        assert_eq!(
+            sanitize.alternate_titles("Sundowning (Deluxe)"),
+            [vec!["sundowning".to_owned()]]
+        );
+        assert_eq!(
+            sanitize.alternate_titles("Abbey Road - Remastered 2009"),
+            [vec!["abbey".to_owned(), "road".to_owned()]]
+        );
+        assert!(sanitize.alternate_titles("Sundowning").is_empty());
+        assert!(sanitize.alternate_titles("(What's the Story)").is_empty());
+    }
+
+    #[test]
+    fn accents_dropped() {
+        let (words, _) = pick_one("Café Tacvba", Sanitize::default());
+        assert_eq!(words, ["cafe", "tacvba"]);
+        assert_eq!(Sanitize::default().words("Mötley Crüe"), ["motley", "crue"]);
+    }
+
+    #[test]
+    fn apostrophes_kept() {
+        let sanitize = Sanitize {
+            keep_apostrophes: true,
+        };
+        let (words, len) = pick_one("Caligula’s Horse", sanitize);
+        assert_eq!(words, ["caligula's", "horse"]);
+        assert_eq!(len, [10, 5]);
+
+        use Grade::*;
+        assert_eq!(
+            grade(&words[0], "caligula's").unwrap(),
+            [Correct; 10].to_vec()
+        );
+        assert_eq!(
+            grade(&words[0], "caligulas'").unwrap(),
+            [
+                Correct, Correct, Correct, Correct, Correct, Correct, Correct, Correct, WrongPlace,
+                WrongPlace
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_rejects_long_names() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let candidates = [
+            ("The Mountain (Deluxe Edition) [Remastered]", 1000),
+            ("Vector", 1),
+        ];
+        for _ in 0..100 {
+            let (picked, _) = pick_word(
+                candidates,
+                max_len(Some(10)),
+                Sanitize::default(),
+                Selection::Popular,
+                &mut rng,
+            )
+            .unwrap();
+            assert_eq!(picked.0, "Vector");
+        }
+    }
+
+    #[test]
+    fn min_words_skips_short_names() {
+        let filter = min_words(Some(2), Sanitize::default());
+        assert!(!filter(&("Vector", 1)));
+        assert!(filter(&("Images and Words", 1)));
+        // punctuation on its own is not a word
+        assert!(!filter(&("Vector -", 1)));
+        assert!(min_words(None, Sanitize::default())(&("Vector", 1)));
+    }
+
+    fn images(name: &str) -> Vec<Image> {
+        vec![
+            Image {
+                size: ImageSize::Small,
+                url: format!("https://example.com/{name}-small.png"),
+            },
+            Image {
+                size: ImageSize::Large,
+                url: format!("https://example.com/{name}-large.png"),
+            },
+        ]
+    }
+
+    #[test]
+    fn puzzle_numbers_count_days() {
+        let epoch = time::macros::date!(2025 - 01 - 01);
+        assert_eq!(puzzle_number(epoch, epoch), 1);
+        let mut day = epoch;
+        for n in 1..=400 {
+            assert_eq!(puzzle_number(epoch, day), n);
+            day = day.next_day().unwrap();
+        }
+        assert_eq!(
+            puzzle_number(epoch, time::macros::date!(2026 - 10 - 16)),
+            654
+        );
+    }
+
+    #[test]
+    fn grade_codes_round_trip() {
+        for grade in [Grade::Incorrect, Grade::WrongPlace, Grade::Correct] {
+            assert_eq!(grade.as_code().parse(), Ok(grade));
+            assert_eq!(Grade::try_from(grade.to_string().as_str()), Ok(grade));
+            assert_eq!(format!("{grade:?}").parse(), Ok(grade));
+        }
+    }
+
+    #[test]
+    fn parse_grades() {
+        let parse = |s: &str| s.parse::<Grade>();
+        assert_eq!(parse("Correct"), Ok(Grade::Correct));
+        assert_eq!(parse("WrongPlace"), Ok(Grade::WrongPlace));
+        assert_eq!(parse("Incorrect"), Ok(Grade::Incorrect));
+        assert_eq!(parse("C"), Ok(Grade::Correct));
+        assert_eq!(parse("W"), Ok(Grade::WrongPlace));
+        assert_eq!(parse("I"), Ok(Grade::Incorrect));
+        assert_eq!(parse("🟩"), Ok(Grade::Correct));
+        assert_eq!(parse("🟨"), Ok(Grade::WrongPlace));
+        assert_eq!(parse("⬛"), Ok(Grade::Incorrect));
+        assert_eq!(parse("⬜"), Ok(Grade::Incorrect));
+        assert_eq!(parse("correct"), Err(ParseGradeError("correct".into())));
+        assert_eq!(parse(""), Err(ParseGradeError(String::new())));
+    }
+
+    fn short_artist() -> ShortArtist {
+        ShortArtist {
+            name: "Haken".into(),
+            mbid: String::new(),
+            url: "https://www.last.fm/music/Haken".parse().unwrap(),
+            images: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn album_reveal_image() {
+        let seed = Seed::from(Album {
+            rank: Some(1),
+            name: "Vector".into(),
+            playcount: 1,
+            mbid: String::new(),
+            url: "https://www.last.fm/music/Haken/Vector".parse().unwrap(),
+            artist: short_artist(),
+            images: images("vector"),
+        });
+        assert_eq!(seed.name(), "Vector");
+        assert_eq!(seed.image(), Some("https://example.com/vector-large.png"));
+    }
+
+    #[test]
+    fn track_reveal_image() {
+        let seed = Seed::from(Track {
+            rank: Some(1),
+            name: "Puzzle Box".into(),
+            duration: 0,
+            playcount: 1,
+            mbid: String::new(),
+            url: "https://www.last.fm/music/Haken/_/Puzzle+Box"
+                .parse()
+                .unwrap(),
+            streamable: false,
+            artist: short_artist(),
+            images: images("puzzle-box"),
+        });
+        assert_eq!(
+            seed.image(),
+            Some("https://example.com/puzzle-box-large.png")
+        );
+    }
+
+    #[test]
+    fn artist_reveal_image() {
+        let seed = Seed::from(Artist {
+            rank: Some(1),
+            name: "Haken".into(),
+            playcount: 1,
+            mbid: String::new(),
+            url: "https://www.last.fm/music/Haken".parse().unwrap(),
+            streamable: false,
+            images: images("haken"),
+        });
+        assert_eq!(seed.image(), Some("https://example.com/haken-large.png"));
+    }
+}
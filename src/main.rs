@@ -1,37 +1,100 @@
+// The in-memory state is behind `std::sync` locks rather than async ones. They are only held for
+// quick map accesses, never across an `.await`, so they cannot stall the runtime; this keeps it so.
+#![deny(clippy::await_holding_lock)]
+
 use std::{
     collections::{HashMap, HashSet},
-    env,
+    future,
+    hash::Hash,
     path::Path,
-    sync::{Arc, Mutex, RwLock},
+    sync::{Arc, LazyLock, RwLock},
+    time::{Duration, Instant},
 };
 
 use axum::{
     Json, Router,
+    body::Body,
     extract::{Query, State},
     http::{
-        HeaderValue, StatusCode,
+        HeaderMap, HeaderValue, StatusCode,
         header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
     },
-    response::{IntoResponse, Redirect},
+    response::{IntoResponse, Redirect, Response},
     routing::{get, post},
 };
 use axum_extra::extract::{CookieJar, cookie::Cookie};
-use hmac::{Hmac, Mac};
+use futures_util::{StreamExt, stream};
+use hmac::Hmac;
 use jwt::{SignWithKey as _, VerifyWithKey as _};
-use rand::seq::IteratorRandom;
+use lastfm::{
+    api_types::{Album, Period, TopAlbums},
+    cache::TtlCache,
+};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use sha2::Sha512;
 use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use time::OffsetDateTime;
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use ts_rs::TS;
 use uuid::Uuid;
 
+use crate::{
+    config::Config,
+    game::{
+        Candidate, DrawRng, Grade, GradingError, Sanitize, Seed, SeedSource, Selection,
+        difficulty_score, grade, max_len, min_words, pick_word, puzzle_number, share_checksum,
+        share_checksum_matches, word_lengths,
+    },
+    sessions::{SessionStore, Sessions},
+};
+
+mod config;
+mod game;
+mod sessions;
+
+/// Guesses a game allows after the first one.
 const MAX_GUESSES: usize = 6;
+/// How long a game created for an `Idempotency-Key` is handed out again for the same key.
+const IDEMPOTENCY_TTL: Duration = Duration::from_secs(10 * 60);
+/// How long a user has to wait between two refreshes of their Last.fm data.
+const REFRESH_COOLDOWN: Duration = Duration::from_secs(60);
+/// How long top albums fetched by [`warm_up`] are used instead of asking Last.fm.
+const PREFETCH_TTL: Duration = Duration::from_secs(60 * 60);
+/// How long the release year of an album is remembered for the reveal.
+const RELEASE_YEAR_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// How long the reveal waits for Last.fm to tell the release year before it is sent without one.
+const RELEASE_YEAR_TIMEOUT: Duration = Duration::from_secs(2);
+/// How many users [`warm_up`] fetches the top albums of at once.
+const WARMUP_CONCURRENCY: usize = 4;
+/// The version of the [`StoredSession`] layout, bumped whenever it changes.
+const SESSION_SCHEMA_VERSION: u32 = 5;
+
+/// The same moment on tokio's clock and on the wall clock, to convert between the two.
+///
+/// Games measure their time with tokio's clock, which tests can pause, but have to be stored with
+/// a wall-clock time to survive restarts.
+static CLOCK_ANCHOR: LazyLock<(tokio::time::Instant, OffsetDateTime)> =
+    LazyLock::new(|| (tokio::time::Instant::now(), OffsetDateTime::now_utc()));
+
+fn to_wall_clock(instant: tokio::time::Instant) -> OffsetDateTime {
+    let (anchor, wall_clock) = *CLOCK_ANCHOR;
+    if instant >= anchor {
+        wall_clock + (instant - anchor)
+    } else {
+        wall_clock - (anchor - instant)
+    }
+}
 
-struct Config {
-    lastfm_apikey: String,
-    auth_callback_url: String,
-    jwt_key: Hmac<Sha512>,
+fn from_wall_clock(time: OffsetDateTime) -> tokio::time::Instant {
+    let (anchor, wall_clock) = *CLOCK_ANCHOR;
+    let offset = time - wall_clock;
+    let since = Duration::try_from(offset.abs()).unwrap_or_default();
+    if offset.is_negative() {
+        anchor.checked_sub(since).unwrap_or(anchor)
+    } else {
+        anchor + since
+    }
 }
 
 #[derive(Clone)]
@@ -40,22 +103,285 @@ struct WordList(&'static [HashSet<&'static str>]);
 #[derive(Clone)]
 struct SharedState {
     mutable: Arc<RwLock<AppState>>,
+    /// Top albums fetched ahead by [`warm_up`], by user and period.
+    prefetched: Arc<TtlCache<(String, Period), TopAlbums>>,
+    /// Release years looked up by [`release_year`], by artist and album name.
+    release_years: Arc<TtlCache<(String, String), Option<i32>>>,
+    sessions: Arc<Sessions>,
     config: Arc<Config>,
     pool: SqlitePool,
     lastfm: Arc<lastfm::Client>,
+    rng: DrawRng,
 
     word_list: WordList,
 }
 
+/// Bookkeeping of recent requests. Guarded by a `std::sync::RwLock` that must not be held across
+/// an `.await`; do any Last.fm or database calls before taking the lock.
 #[derive(Default)]
 struct AppState {
-    db: HashMap<Uuid, Mutex<SessionState>>,
+    /// When each user last refreshed their Last.fm data.
+    refreshed: HashMap<String, Instant>,
 }
 
-#[derive(Clone)]
+/// Fetch the top albums of the users in [`Config::warmup_users`], so that their games are
+/// drawn without waiting for Last.fm.
+async fn warm_up(state: SharedState) {
+    let period = state.config.default_period;
+    stream::iter(state.config.warmup_users.clone())
+        .map(|user| {
+            let state = state.clone();
+            async move {
+                let request = state.lastfm.top_albums(&user).period(period).send();
+                let res = lastfm_call(&state.config, request).await;
+                (user, res)
+            }
+        })
+        .buffer_unordered(WARMUP_CONCURRENCY)
+        .for_each(|(user, res)| {
+            match res {
+                Ok(top) => state.prefetched.insert((user, period), top),
+                Err(e) => log::warn!("could not warm up top albums of {user}: {e}"),
+            }
+            future::ready(())
+        })
+        .await;
+}
+
+/// The top albums of `user` in `period`, from [`warm_up`] if it fetched them.
+async fn user_top_albums(
+    state: &SharedState,
+    user: &str,
+    period: Period,
+) -> Result<TopAlbums, AppError> {
+    if let Some(top) = state.prefetched.get(&(user.to_owned(), period)) {
+        return Ok(top);
+    }
+    let request = state.lastfm.top_albums(user).period(period).send();
+    lastfm_call(&state.config, request).await
+}
+
+/// Keep a new game for `words` in the session store, set up as asked for in `query`.
+async fn start_game(
+    state: &SharedState,
+    key: Option<IdempotencyKey>,
+    query: &NewGameQuery,
+    seed: Seed,
+    source: Option<SeedSource>,
+    puzzle_number: i64,
+    mut words: Vec<String>,
+) -> Result<NewGameResult, AppError> {
+    let mut alternates = query.alternates(seed.name());
+    // an empty word would be shown as a board without tiles that no guess can fill
+    words.retain(|word| !word.is_empty());
+    for alternate in &mut alternates {
+        alternate.retain(|word| !word.is_empty());
+    }
+    alternates.retain(|alternate| !alternate.is_empty());
+    let id = Uuid::new_v4();
+    // derived from the stored words, so that the board always matches what is graded
+    let len = word_lengths(&words);
+    let difficulty = difficulty_score(&words);
+    state
+        .sessions
+        .insert(
+            id,
+            SessionState::new(seed, words, alternates, query.sanitize(), query.practice),
+        )
+        .await?;
+    let game = NewGameResult {
+        id,
+        len,
+        difficulty,
+        source,
+        puzzle_number,
+    };
+    if let Some(key) = key {
+        remember_game(state, &key, &game).await;
+    }
+    Ok(game)
+}
+
+/// An `Idempotency-Key`, which only replays games of the same endpoint and user.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct IdempotencyKey {
+    endpoint: &'static str,
+    user: String,
+    key: String,
+}
+
+fn idempotency_key(
+    headers: &HeaderMap,
+    endpoint: &'static str,
+    user: &str,
+) -> Option<IdempotencyKey> {
+    let key = headers.get("idempotency-key")?.to_str().ok()?;
+    Some(IdempotencyKey {
+        endpoint,
+        user: user.to_owned(),
+        key: key.to_owned(),
+    })
+}
+
+/// The game previously created for `key`, if it is still fresh. Kept in the database, so that
+/// retries reaching another instance, or the same one after a restart, get it as well.
+async fn replay(
+    state: &SharedState,
+    key: Option<&IdempotencyKey>,
+) -> Result<Option<NewGameResult>, AppError> {
+    let Some(key) = key else {
+        return Ok(None);
+    };
+    let ttl = IDEMPOTENCY_TTL.as_secs() as i64;
+    let game = sqlx::query_scalar!(
+        "SELECT game FROM idempotent_game
+        WHERE endpoint = ? AND lastfm_name = ? AND key = ? AND created_at > unixepoch() - ?",
+        key.endpoint,
+        key.user,
+        key.key,
+        ttl
+    )
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(AppError::internal)?;
+    game.map(|game| serde_json::from_str(&game).map_err(AppError::internal))
+        .transpose()
+}
+
+/// Hand out `game` again for retries with the same `key`. Failing to do so is only logged, as
+/// the game was created either way.
+async fn remember_game(state: &SharedState, key: &IdempotencyKey, game: &NewGameResult) {
+    let ttl = IDEMPOTENCY_TTL.as_secs() as i64;
+    let json = serde_json::to_string(game).expect("can serialize");
+    let expired = sqlx::query!(
+        "DELETE FROM idempotent_game WHERE created_at <= unixepoch() - ?",
+        ttl
+    )
+    .execute(&state.pool);
+    let inserted = sqlx::query!(
+        "INSERT INTO idempotent_game (endpoint, lastfm_name, key, game, created_at)
+        VALUES (?, ?, ?, ?, unixepoch())
+        ON CONFLICT (endpoint, lastfm_name, key) DO UPDATE
+        SET game = excluded.game, created_at = excluded.created_at",
+        key.endpoint,
+        key.user,
+        key.key,
+        json
+    )
+    .execute(&state.pool);
+    if let Err(e) = expired.await.and(inserted.await) {
+        log::error!("failed to remember game for its idempotency key: {e}");
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(into = "StoredSession", try_from = "StoredSession")]
 struct SessionState {
+    /// What the answer was drawn from.
+    seed: Seed,
     words: Vec<String>,
+    /// Other titles the answer is accepted as, e.g. without its `(Deluxe)` suffix.
+    alternates: Vec<Vec<String>>,
     num_guesses: usize,
+    history: Vec<GuessRecord>,
+    /// Measured on the server, so that clients cannot fake their solving times.
+    started_at: tokio::time::Instant,
+    /// How guesses are split into words, the same way as the answer.
+    sanitize: Sanitize,
+    /// Guesses can be previewed, and the game is left out of the stats.
+    practice: bool,
+}
+
+/// A [`SessionState`] as persisted, e.g. in a JSON column.
+///
+/// The start of the game cannot be stored as an [`Instant`](tokio::time::Instant), so it is
+/// kept as a wall-clock time, see [`CLOCK_ANCHOR`].
+#[derive(Serialize, Deserialize)]
+struct StoredSession {
+    version: u32,
+    seed: Seed,
+    words: Vec<String>,
+    /// Missing from version 1, which had no alternate titles.
+    #[serde(default)]
+    alternates: Vec<Vec<String>>,
+    num_guesses: usize,
+    history: Vec<GuessRecord>,
+    /// Missing before version 4.
+    #[serde(default)]
+    started_at: Option<OffsetDateTime>,
+    /// How long the game had been running when it was stored, which versions before 4 kept
+    /// instead of [`StoredSession::started_at`].
+    #[serde(default, skip_serializing)]
+    elapsed_ms: Option<u64>,
+    /// Missing before version 3, which had no practice games.
+    #[serde(default)]
+    practice: bool,
+    /// [`Sanitize::keep_apostrophes`]. Missing before version 5, whose games kept apostrophes
+    /// exactly when their answer has some.
+    #[serde(default)]
+    keep_apostrophes: Option<bool>,
+}
+
+impl From<SessionState> for StoredSession {
+    fn from(state: SessionState) -> Self {
+        StoredSession {
+            version: SESSION_SCHEMA_VERSION,
+            started_at: Some(to_wall_clock(state.started_at)),
+            elapsed_ms: None,
+            seed: state.seed,
+            words: state.words,
+            alternates: state.alternates,
+            num_guesses: state.num_guesses,
+            history: state.history,
+            practice: state.practice,
+            keep_apostrophes: Some(state.sanitize.keep_apostrophes),
+        }
+    }
+}
+
+impl TryFrom<StoredSession> for SessionState {
+    type Error = String;
+
+    fn try_from(stored: StoredSession) -> Result<Self, Self::Error> {
+        if !(1..=SESSION_SCHEMA_VERSION).contains(&stored.version) {
+            return Err(format!("unsupported session version {}", stored.version));
+        }
+        let now = tokio::time::Instant::now();
+        let started_at = match (stored.started_at, stored.elapsed_ms) {
+            (Some(started_at), _) => from_wall_clock(started_at),
+            (None, Some(elapsed_ms)) => now
+                .checked_sub(Duration::from_millis(elapsed_ms))
+                .unwrap_or(now),
+            (None, None) => now,
+        };
+        let keep_apostrophes = stored
+            .keep_apostrophes
+            .unwrap_or_else(|| stored.words.iter().any(|word| word.contains('\'')));
+        Ok(SessionState {
+            seed: stored.seed,
+            words: stored.words,
+            alternates: stored.alternates,
+            num_guesses: stored.num_guesses,
+            history: stored.history,
+            started_at,
+            sanitize: Sanitize { keep_apostrophes },
+            practice: stored.practice,
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+struct GuessRecord {
+    guess: String,
+    outcome: GuessOutcome,
+}
+
+#[derive(Clone, Serialize, Deserialize, TS)]
+enum GuessOutcome {
+    Graded(Vec<Vec<Grade>>),
+    /// The guess was not a valid attempt and did not count towards the limit.
+    Rejected(GradingError),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -75,15 +401,31 @@ pub enum AppError {
     #[error("too many guesses")]
     TooManyGuesses,
     #[error("internal server error")]
-    Internal(Box<dyn std::error::Error>),
+    Internal(Box<dyn std::error::Error + Send + Sync>),
     #[error("no user to fetch data for")]
     NoUser,
+    #[error("{0}")]
+    InvalidPeriod(lastfm::api_types::ParsePeriodError),
+    #[error("LastFM took too long to respond")]
+    Timeout,
+    #[error("nothing to pick with at least {0} words")]
+    TooFewWords(usize),
+    #[error("admin access required")]
+    NotAdmin,
+    #[error("could not save the session, please sign in again")]
+    SessionStore(sqlx::Error),
+    #[error("another guess for this game is still being graded")]
+    GuessInFlight,
+    #[error("data was refreshed recently, try again later")]
+    RefreshTooSoon,
+    #[error("guesses can only be previewed in practice games")]
+    NotPractice,
 }
 
 impl AppError {
     fn internal<E>(e: E) -> AppError
     where
-        E: std::error::Error + 'static,
+        E: std::error::Error + Send + Sync + 'static,
     {
         AppError::Internal(Box::new(e))
     }
@@ -107,15 +449,28 @@ impl IntoResponse for AppError {
             AppError::UnknownWord(..)
             | AppError::NoAlbums
             | AppError::NoUser
+            | AppError::TooFewWords(..)
             | AppError::MissingParam(..)
+            | AppError::InvalidPeriod(..)
             | AppError::GradingError(..) => StatusCode::BAD_REQUEST,
-            AppError::TooManyGuesses => StatusCode::FORBIDDEN,
+            AppError::TooManyGuesses | AppError::NotAdmin | AppError::NotPractice => {
+                StatusCode::FORBIDDEN
+            }
+            AppError::Timeout => StatusCode::GATEWAY_TIMEOUT,
+            AppError::SessionStore(..) | AppError::LastFm(lastfm::Error::Maintenance) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            AppError::GuessInFlight => StatusCode::CONFLICT,
+            AppError::RefreshTooSoon => StatusCode::TOO_MANY_REQUESTS,
             AppError::LastFm(_) | AppError::Internal(..) => StatusCode::INTERNAL_SERVER_ERROR,
         };
 
         if let AppError::Internal(ref e) = self {
             log::error!("interal server error {e}");
         }
+        if let AppError::SessionStore(ref e) = self {
+            log::error!("failed to store session {e}");
+        }
 
         (
             status,
@@ -127,63 +482,23 @@ impl IntoResponse for AppError {
     }
 }
 
-#[derive(
-    Debug, PartialEq, Eq, Default, Clone, Copy, Hash, PartialOrd, Ord, Serialize, Deserialize, TS,
-)]
-pub enum Grade {
-    #[default]
-    Incorrect,
-    WrongPlace,
-    Correct,
-}
-
-#[derive(thiserror::Error, Debug, Serialize, TS)]
-pub enum GradingError {
-    #[error("Wrong length (expected {0}, have {1})")]
-    WrongLength(usize, usize),
-    #[error("Wrong number of words (expected {0}, have {1})")]
-    WrongNumberOfWords(usize, usize),
-}
-
-fn grade(expected: &str, guess: &str) -> Result<Vec<Grade>, GradingError> {
-    if expected.len() != guess.len() {
-        return Err(GradingError::WrongLength(expected.len(), guess.len()));
-    }
-
-    let mut word: Vec<_> = guess.chars().map(Some).collect();
-    let mut expected: Vec<_> = expected.chars().map(Some).collect();
-
-    let mut ret = vec![Grade::Incorrect; expected.len()];
-
-    for (i, (w, e)) in word.iter_mut().zip(expected.iter_mut()).enumerate() {
-        if w == e {
-            ret[i] = Grade::Correct;
-            *w = None;
-            *e = None;
-        }
-    }
-
-    for (i, w) in word.iter().enumerate() {
-        if w.is_none() {
-            continue;
-        }
-        for e in expected.iter_mut() {
-            if w == e {
-                ret[i] = Grade::WrongPlace;
-                *e = None;
-                break;
-            }
-        }
-    }
-
-    Ok(ret)
-}
-
 impl SessionState {
-    fn new(words: Vec<String>) -> Self {
+    fn new(
+        seed: Seed,
+        words: Vec<String>,
+        alternates: Vec<Vec<String>>,
+        sanitize: Sanitize,
+        practice: bool,
+    ) -> Self {
         Self {
+            seed,
             words,
+            alternates,
             num_guesses: 0,
+            history: Vec::new(),
+            started_at: tokio::time::Instant::now(),
+            sanitize,
+            practice,
         }
     }
 }
@@ -205,7 +520,11 @@ impl WordList {
         Self(Box::leak(v.into_boxed_slice()))
     }
 
+    /// Whether `w` is a known word. Apostrophes are ignored, as the list has none.
     fn contains(&self, w: &str) -> bool {
+        if w.contains('\'') {
+            return self.contains(&w.replace('\'', ""));
+        }
         self.0.get(w.len()).is_some_and(|list| list.contains(w))
     }
 }
@@ -215,41 +534,50 @@ async fn main() {
     tracing_subscriber::fmt::init();
 
     dotenvy::dotenv().expect("have dotenv");
-    fn var(name: &str) -> String {
-        env::var(name).unwrap_or_else(|_| panic!("{name} must be set"))
-    }
 
-    let config = Arc::new(Config {
-        lastfm_apikey: var("LASTFM_APIKEY"),
-        auth_callback_url: var("AUTH_CALLBACK_URL"),
-        jwt_key: Hmac::new_from_slice(var("JWT_KEY").as_bytes()).expect("create new key"),
-    });
+    let config = Arc::new(Config::load().unwrap_or_else(|e| panic!("{e}")));
     let mutable = Default::default();
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL is set");
     let pool = SqlitePoolOptions::new()
-        .connect(&database_url)
+        .connect(&config.database_url)
         .await
         .expect("can connect to db");
-    let lastfm = Arc::new(lastfm::Client::new(
-        var("LASTFM_SHARED_SECRET"),
-        var("LASTFM_APIKEY"),
-    ));
+    let sessions = Arc::new(Sessions::new(config.session_backend, pool.clone()));
+    let lastfm = Arc::new(
+        lastfm::Client::new(
+            config.lastfm_shared_secret.clone(),
+            config.lastfm_apikey.clone(),
+        )
+        .unwrap_or_else(|e| panic!("{e}")),
+    );
     let state = SharedState {
         mutable: Arc::clone(&mutable),
+        prefetched: Arc::new(TtlCache::new(PREFETCH_TTL)),
+        release_years: Arc::new(TtlCache::new(RELEASE_YEAR_TTL)),
+        sessions,
         config: Arc::clone(&config),
         pool,
         lastfm,
+        rng: DrawRng::default(),
         word_list: WordList::new("./wordlist.txt"),
     };
+    tokio::spawn(warm_up(state.clone()));
 
     let app = Router::new()
         .route("/", get(root))
         .route("/api/v1/newgame", post(newgame))
         .route("/api/v1/newgame-album", post(newgame_album))
         .route("/api/v1/guess", post(guess))
+        .route("/api/v1/guess-tiles", post(guess_tiles))
+        .route("/api/v1/preview-guess", post(preview_guess))
+        .route("/api/v1/verify-share", post(verify_share))
+        .route("/api/v1/history", get(history))
+        .route("/api/v1/refresh", post(refresh))
         .route("/api/v1/top-albums", get(get_top_albums))
         .route("/api/v1/signin", get(signin))
         .route("/api/v1/auth-url", get(get_auth_url))
+        .route("/api/v1/version", get(version))
+        .route("/api/v1/export", get(export))
+        .route("/api/v1/album-stats", get(album_stats))
         .layer(TraceLayer::new_for_http())
         .layer(
             CorsLayer::new()
@@ -313,6 +641,22 @@ async fn signin(
         .await
         .map_err(AppError::LastFm)?;
 
+    store_session(&state.pool, &session)
+        .await
+        .map_err(AppError::SessionStore)?;
+
+    let jar = sign_cookie(&state.config.jwt_key, session.name, 4 * 7, jar)?;
+
+    Ok((jar, Redirect::to("/")))
+}
+
+/// Persist a freshly authenticated session in a single transaction, so a failure leaves no
+/// partial row behind and the client can simply retry the callback.
+async fn store_session(
+    pool: &SqlitePool,
+    session: &lastfm::api_types::Session,
+) -> sqlx::Result<()> {
+    let mut tx = pool.begin().await?;
     sqlx::query!(
         "INSERT INTO user
             (lastfm_name, lastfm_key, auth_at, lastfm_subscriber)
@@ -324,13 +668,20 @@ async fn signin(
         session.key,
         session.subscriber
     )
-    .execute(&state.pool)
-    .await
-    .map_err(|x| AppError::Internal(Box::new(x)))?;
-
-    let jar = sign_cookie(&state.config.jwt_key, session.name, 4 * 7, jar)?;
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await
+}
 
-    Ok((jar, Redirect::to("/")))
+/// Await a Last.fm request, giving up after the configured timeout.
+async fn lastfm_call<T>(
+    config: &Config,
+    request: impl Future<Output = Result<T, lastfm::Error>>,
+) -> Result<T, AppError> {
+    tokio::time::timeout(config.lastfm_timeout, request)
+        .await
+        .map_err(|_| AppError::Timeout)?
+        .map_err(AppError::LastFm)
 }
 
 #[axum::debug_handler]
@@ -342,91 +693,378 @@ async fn get_top_albums(
         .lastfm
         .top_albums(query.get("user").ok_or(AppError::MissingParam("user"))?)
         .send();
-    x.await.map_err(AppError::LastFm).map(Json)
+    lastfm_call(&state.config, x).await.map(Json)
 }
 
-#[derive(Serialize, TS)]
+#[derive(Serialize, Deserialize, TS, Clone)]
 #[ts(export)]
 struct NewGameResult {
     id: Uuid,
     len: Vec<usize>,
-}
-
-fn pick_word(it: impl IntoIterator<Item = String>) -> Result<(Vec<String>, Vec<usize>), AppError> {
-    let mut rng = rand::rng();
-    let word = it
-        .into_iter()
-        .filter(|word| {
-            word.chars()
-                .any(|ch| matches!(ch, 'a'..='z' | 'A'..='Z' | '0'..='9'))
-        })
-        .choose(&mut rng)
-        .ok_or(AppError::NoAlbums)?;
-    let word: String = word
-        .chars()
-        .filter_map(|ch| match ch {
-            'a'..='z' | '0'..='9' => Some(ch),
-            'A'..='Z' => Some(ch.to_ascii_lowercase()),
-            ch if ch.is_whitespace() => Some(ch),
-            _ => None,
-        })
-        .collect();
-    let words: Vec<_> = word.split_whitespace().map(ToOwned::to_owned).collect();
-    let len = words.iter().map(|x| x.len()).collect();
-    Ok((words, len))
+    /// How hard the answer is expected to be, see [`difficulty_score`].
+    difficulty: f64,
+    /// What the answer pool came from. Artist games are always drawn from the user's top artists.
+    source: Option<SeedSource>,
+    /// The number of today's puzzle, for share texts like "Bandordle #123".
+    puzzle_number: i64,
 }
 
 // TODO: temporary until we have users
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 struct NewGameQuery {
     user: Option<String>,
+    /// Comma separated list of periods whose favorites make up the answer pool, e.g.
+    /// `overall,1month`.
+    periods: Option<String>,
+    /// Keep apostrophes in the answer instead of dropping them.
+    #[serde(default)]
+    apostrophes: bool,
+    /// How the answer is drawn from the pool, e.g. `popular` to favor the most played.
+    #[serde(default)]
+    selection: Selection,
+    /// Only pick answers with at least this many words.
+    min_words: Option<usize>,
+    /// Also accept answers without their edition suffixes, e.g. "Sundowning" for
+    /// "Sundowning (Deluxe)".
+    #[serde(default)]
+    forgiving: bool,
+    /// Allow previewing the grade of guesses, leaving the game out of the stats.
+    #[serde(default)]
+    practice: bool,
+}
+
+impl NewGameQuery {
+    fn periods(&self, default: Period) -> Result<Vec<Period>, AppError> {
+        let Some(periods) = &self.periods else {
+            return Ok(vec![default]);
+        };
+        periods
+            .split(',')
+            .map(|period| period.trim().parse().map_err(AppError::InvalidPeriod))
+            .collect()
+    }
+
+    fn sanitize(&self) -> Sanitize {
+        Sanitize {
+            keep_apostrophes: self.apostrophes,
+        }
+    }
+
+    /// The titles besides `name` that are accepted in a forgiving game.
+    fn alternates(&self, name: &str) -> Vec<Vec<String>> {
+        if !self.forgiving {
+            return Vec::new();
+        }
+        self.sanitize().alternate_titles(name)
+    }
+
+    /// Draw the answer from `pool` as requested, avoiding the `recent` answer keys if possible.
+    fn draw<T: Candidate>(
+        &self,
+        config: &Config,
+        pool: Vec<T>,
+        recent: &[String],
+        rng: &mut impl Rng,
+    ) -> Result<(T, Vec<String>), AppError> {
+        let sanitize = self.sanitize();
+        let min_words = min_words(self.min_words, sanitize);
+        if let Some(min) = self.min_words
+            && !pool.iter().any(&min_words)
+        {
+            return Err(AppError::TooFewWords(min));
+        }
+        let max_len = max_len(config.max_answer_len);
+        let accept = |candidate: &T| max_len(candidate) && min_words(candidate);
+        let fresh = |candidate: &T| accept(candidate) && !recent.contains(&candidate.key());
+        // with a pool this small, repeating an answer beats having none
+        let avoid_recent = pool.iter().any(fresh);
+        pick_word(
+            pool,
+            |candidate| {
+                if avoid_recent {
+                    fresh(candidate)
+                } else {
+                    accept(candidate)
+                }
+            },
+            sanitize,
+            self.selection,
+            rng,
+        )
+    }
+}
+
+/// Concatenate `lists`, keeping only the first item for each key.
+fn merge_dedup<T, K>(lists: impl IntoIterator<Item = Vec<T>>, key: impl Fn(&T) -> K) -> Vec<T>
+where
+    K: Eq + Hash,
+{
+    let mut seen = HashSet::new();
+    lists
+        .into_iter()
+        .flatten()
+        .filter(|item| seen.insert(key(item)))
+        .collect()
+}
+
+/// What makes two albums the same when merging lists of them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum AlbumKey {
+    Mbid(String),
+    /// The artist and album names, lowercased and with their whitespace collapsed.
+    Names(String, String),
+}
+
+/// The mbid of `album` if it has one, otherwise its normalized artist and album names.
+fn album_key(album: &Album) -> AlbumKey {
+    if !album.mbid.is_empty() {
+        return AlbumKey::Mbid(album.mbid.clone());
+    }
+    let normalize = |name: &str| {
+        name.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    AlbumKey::Names(normalize(&album.artist.name), normalize(&album.name))
+}
+
+/// Warn if Last.fm answered for another period than `requested`, which it does silently when it
+/// clamps a period it cannot serve. Returns whether the periods matched; responses without a
+/// period are assumed to match.
+fn check_echoed_period(user: &str, requested: Period, echoed: Option<Period>) -> bool {
+    match echoed {
+        Some(echoed) if echoed != requested => {
+            log::warn!(
+                "requested top list of {user} for {requested}, last.fm answered for {echoed}"
+            );
+            false
+        }
+        _ => true,
+    }
+}
+
+/// Keys of the answers `user` got in their last games, as many as the config says to avoid.
+async fn recent_answers(state: &SharedState, user: &str) -> Result<Vec<String>, AppError> {
+    let limit = state.config.avoid_recent_answers as i64;
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+    sqlx::query_scalar!(
+        "SELECT answer_key FROM recent_answer
+        WHERE lastfm_name = ?
+        ORDER BY id DESC
+        LIMIT ?",
+        user,
+        limit
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(AppError::internal)
+}
+
+async fn record_answer(state: &SharedState, user: &str, key: &str) -> Result<(), AppError> {
+    if state.config.avoid_recent_answers == 0 {
+        return Ok(());
+    }
+    sqlx::query!(
+        "INSERT INTO recent_answer
+            (lastfm_name, answer_key, created_at)
+        VALUES (
+            ?, ?, unixepoch()
+        );
+        ",
+        user,
+        key
+    )
+    .execute(&state.pool)
+    .await
+    .map_err(AppError::internal)?;
+    Ok(())
+}
+
+/// The Last.fm name of the player signed in with the `session` cookie, if it is valid.
+fn signed_in_user(jar: &CookieJar, key: &Hmac<Sha512>) -> Option<String> {
+    let claims: JwtClaims = jar.get("session")?.value().verify_with_key(key).ok()?;
+    Some(claims.fmname)
+}
+
+/// Fill in the settings the signed in `player` left out with those of their previous game, and
+/// remember the ones they gave for the next.
+///
+/// Nothing is remembered for players who are not signed in, as anyone can claim a `?user=`.
+async fn remember_settings(
+    state: &SharedState,
+    player: Option<&str>,
+    query: &mut NewGameQuery,
+) -> Result<(), AppError> {
+    let Some(user) = player.filter(|_| state.config.remember_settings) else {
+        return Ok(());
+    };
+    match &query.periods {
+        Some(periods) => {
+            // only remember what can be used again
+            query.periods(state.config.default_period)?;
+            sqlx::query!(
+                "INSERT INTO user_settings (lastfm_name, periods)
+                VALUES (?, ?)
+                ON CONFLICT (lastfm_name) DO UPDATE SET periods = excluded.periods",
+                user,
+                periods
+            )
+            .execute(&state.pool)
+            .await
+            .map_err(AppError::internal)?;
+        }
+        None => {
+            query.periods = sqlx::query_scalar!(
+                "SELECT periods FROM user_settings WHERE lastfm_name = ?",
+                user
+            )
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(AppError::internal)?;
+        }
+    }
+    Ok(())
 }
 
 async fn newgame(
     jar: CookieJar,
-    Query(query): Query<NewGameQuery>,
+    headers: HeaderMap,
+    Query(mut query): Query<NewGameQuery>,
     State(state): State<SharedState>,
 ) -> Result<Json<NewGameResult>, AppError> {
     // TODO: user middleware
-    let user = query.user.or(jar.get("session").and_then(|session| {
-        session
-            .value()
-            .verify_with_key(&state.config.jwt_key)
-            .ok()
-            .map(|claims: JwtClaims| claims.fmname)
-    }));
+    let player = signed_in_user(&jar, &state.config.jwt_key);
+    let user = query.user.clone().or(player.clone());
     let user = user.ok_or(AppError::NoUser)?;
+    let key = idempotency_key(&headers, "newgame", &user);
+    if let Some(game) = replay(&state, key.as_ref()).await? {
+        return Ok(Json(game));
+    }
     log::info!("creating new game (artist) for {user}");
-    let resp = state
-        .lastfm
-        .top_artists(&user)
-        .send()
-        .await
-        .map_err(AppError::LastFm)?;
-    let (words, len) = pick_word(resp.artists.into_iter().map(|x| x.name))?;
-
-    let id = Uuid::new_v4();
-    let state = &mut state.mutable.write().unwrap();
-
-    state.db.insert(id, Mutex::new(SessionState::new(words)));
-    Ok(Json(NewGameResult { id, len }))
+    remember_settings(&state, player.as_deref(), &mut query).await?;
+    let periods = query.periods(state.config.default_period)?;
+    let mut pool = Vec::new();
+    for period in periods {
+        let request = state.lastfm.top_artists(&user).period(period).send();
+        let resp = lastfm_call(&state.config, request).await?;
+        check_echoed_period(&user, period, resp.period);
+        pool.push(resp.artists);
+    }
+    let artists = merge_dedup(pool, |artist| artist.name.clone());
+    let recent = recent_answers(&state, &user).await?;
+    let (artist, words) = state
+        .rng
+        .with(|mut rng| query.draw(&state.config, artists, &recent, &mut rng))?;
+    record_answer(&state, &user, &artist.key()).await?;
+
+    let game = start_game(
+        &state,
+        key,
+        &query,
+        artist.into(),
+        None,
+        todays_puzzle(&state.config),
+        words,
+    )
+    .await?;
+    Ok(Json(game))
 }
 
-async fn newgame_album(State(state): State<SharedState>) -> Result<Json<NewGameResult>, AppError> {
-    log::info!("creating new game (album)");
-    let resp = state
-        .lastfm
-        .top_albums("hydehsmf")
-        .send()
-        .await
-        .map_err(AppError::LastFm)?;
-    let (words, len) = pick_word(resp.albums.into_iter().map(|x| x.name))?;
+async fn newgame_album(
+    jar: CookieJar,
+    headers: HeaderMap,
+    Query(mut query): Query<NewGameQuery>,
+    State(state): State<SharedState>,
+) -> Result<Json<NewGameResult>, AppError> {
+    let user = query.user.clone().unwrap_or_else(|| "hydehsmf".into());
+    let key = idempotency_key(&headers, "newgame-album", &user);
+    if let Some(game) = replay(&state, key.as_ref()).await? {
+        return Ok(Json(game));
+    }
+    let player = signed_in_user(&jar, &state.config.jwt_key);
+    remember_settings(&state, player.as_deref(), &mut query).await?;
+    let user = user.as_str();
+    log::info!("creating new game (album) for {user}");
+    let periods = query.periods(state.config.default_period)?;
+    let recent = recent_answers(&state, user).await?;
+    let mut drawn = None;
+    // set if a source was rejected for having too few multi-word answers, to report that instead
+    // of an empty pool
+    let mut too_few_words = None;
+    for &source in &state.config.seed_sources {
+        let attempt = match source {
+            SeedSource::TopAlbums => {
+                let mut pool = Vec::new();
+                for &period in &periods {
+                    let resp = user_top_albums(&state, user, period).await?;
+                    check_echoed_period(user, period, resp.period);
+                    pool.push(resp.albums);
+                }
+                let albums = merge_dedup(pool, album_key);
+                draw_seed(&state, &query, albums, &recent)
+            }
+            SeedSource::RecentTracks => {
+                let request = state.lastfm.recent_tracks(user).send();
+                let resp = lastfm_call(&state.config, request).await?;
+                let tracks = merge_dedup([resp.tracks], |track| {
+                    (track.artist.name.clone(), track.name.clone())
+                });
+                draw_seed(&state, &query, tracks, &recent)
+            }
+            SeedSource::Charts => {
+                let request = state.lastfm.top_tracks_charts().send();
+                let resp = lastfm_call(&state.config, request).await?;
+                draw_seed(&state, &query, resp.tracks, &recent)
+            }
+        };
+        match attempt {
+            Ok(answer) => {
+                drawn = Some((source, answer));
+                break;
+            }
+            Err(AppError::TooFewWords(min)) => too_few_words = Some(min),
+            Err(AppError::NoAlbums) => {}
+            Err(e) => return Err(e),
+        }
+        log::info!("no answer for {user} in {source:?}, falling back");
+    }
+    let Some((source, (answer_key, seed, words))) = drawn else {
+        return Err(too_few_words.map_or(AppError::NoAlbums, AppError::TooFewWords));
+    };
+    log::info!("drew answer for {user} from {source:?}");
+    record_answer(&state, user, &answer_key).await?;
+
+    let game = start_game(
+        &state,
+        key,
+        &query,
+        seed,
+        Some(source),
+        todays_puzzle(&state.config),
+        words,
+    )
+    .await?;
+    Ok(Json(game))
+}
 
-    let id = Uuid::new_v4();
-    let state = &mut state.mutable.write().unwrap();
+fn todays_puzzle(config: &Config) -> i64 {
+    puzzle_number(config.puzzle_epoch, time::OffsetDateTime::now_utc().date())
+}
 
-    state.db.insert(id, Mutex::new(SessionState::new(words)));
-    Ok(Json(NewGameResult { id, len }))
+/// Draw the answer from `pool`, returning its key and the [`Seed`] it came from.
+fn draw_seed<T: Candidate + Into<Seed>>(
+    state: &SharedState,
+    query: &NewGameQuery,
+    pool: Vec<T>,
+    recent: &[String],
+) -> Result<(String, Seed, Vec<String>), AppError> {
+    let (candidate, words) = state
+        .rng
+        .with(|mut rng| query.draw(&state.config, pool, recent, &mut rng))?;
+    Ok((candidate.key(), candidate.into(), words))
 }
 
 #[derive(Deserialize, TS)]
@@ -439,59 +1077,1786 @@ struct GuessArgs {
 #[ts(export)]
 struct GuessResult {
     grade: Vec<Vec<Grade>>,
+    /// The words that were graded, after lowercasing and dropping accents and punctuation.
+    /// Apostrophes are kept if the game was created with them.
+    normalized_guess: Vec<String>,
+    /// Milliseconds since the game was started.
+    #[ts(type = "number")]
+    elapsed_ms: u64,
+    /// How many more guesses the game takes. 0 once it is over.
+    guesses_remaining: usize,
+    /// Set once the game is over.
+    reveal: Option<Reveal>,
+}
+
+/// A [`GuessResult`] with its grades also listed tile by tile, in the order they are revealed.
+#[derive(Serialize, TS)]
+#[ts(export)]
+struct TiledGuessResult {
+    #[serde(flatten)]
+    result: GuessResult,
+    tiles: Vec<Tile>,
+}
+
+/// The grade of one letter of a guess.
+#[derive(Serialize, TS, Debug, PartialEq, Eq)]
+#[ts(export)]
+struct Tile {
+    word_index: usize,
+    char_index: usize,
+    grade: Grade,
+}
+
+/// The grades of all words as one list, word by word and letter by letter within each word.
+fn tiles(grades: &[Vec<Grade>]) -> Vec<Tile> {
+    grades
+        .iter()
+        .enumerate()
+        .flat_map(|(word_index, word)| {
+            word.iter()
+                .enumerate()
+                .map(move |(char_index, &grade)| Tile {
+                    word_index,
+                    char_index,
+                    grade,
+                })
+        })
+        .collect()
+}
+
+/// The answer of a finished game.
+#[derive(Serialize, TS)]
+#[ts(export)]
+struct Reveal {
+    answer: String,
+    /// URL of the artwork of the album, track or artist the answer was drawn from.
+    image: Option<String>,
+    /// When the answer is an album, the year it was released, if Last.fm knows it.
+    release_year: Option<i32>,
+    /// Proves that the board was played to this answer, see [`verify_share`]. Practice games
+    /// have none.
+    share_checksum: Option<String>,
 }
 
 async fn guess(
     State(full_state): State<SharedState>,
     Json(guess): Json<GuessArgs>,
 ) -> Result<Json<GuessResult>, AppError> {
-    fn inner(
-        full_state: &SharedState,
-        guess: GuessArgs,
-        should_delete: &mut bool,
-    ) -> Result<GuessResult, AppError> {
-        let words: Vec<_> = guess.guess.split_whitespace().collect();
-        let st = full_state.mutable.read().unwrap();
-        let state = st.db.get(&guess.id).ok_or(AppError::NoSession)?;
-        let mut state = state.lock().unwrap();
+    let mut ret = full_state
+        .sessions
+        .update(guess.id, |state| {
+            grade_guess(&full_state, state, &guess.guess)
+        })
+        .await?;
+
+    // only finished games are revealed
+    if ret.reveal.is_some() {
+        let finished = full_state.sessions.remove(guess.id).await?;
+        if let Some(finished) = finished {
+            let solved = ret.grade.iter().flatten().all(|x| *x == Grade::Correct);
+            if !finished.practice {
+                record_completion(&full_state, &finished, solved).await;
+            }
+            if let Some(reveal) = &mut ret.reveal {
+                reveal.release_year = release_year(&full_state, &finished.seed).await;
+            }
+        }
+    }
+
+    Ok(Json(ret))
+}
 
-        if state.words.len() != words.len() {
-            return Err(AppError::GradingError(GradingError::WrongNumberOfWords(
-                state.words.len(),
-                words.len(),
-            )));
+/// Grade `guess` in the game `state` and record it.
+fn grade_guess(
+    full_state: &SharedState,
+    state: &mut SessionState,
+    guess: &str,
+) -> Result<GuessResult, AppError> {
+    let normalized_guess = state.sanitize.words(guess);
+    let grade = match best_grade(full_state, state, &normalized_guess) {
+        Err(AppError::GradingError(e)) if full_state.config.record_rejected_guesses => {
+            state.history.push(GuessRecord {
+                guess: guess.to_owned(),
+                outcome: GuessOutcome::Rejected(e.clone()),
+            });
+            return Err(AppError::GradingError(e));
         }
+        graded => graded?,
+    };
+
+    state.num_guesses += 1;
+    state.history.push(GuessRecord {
+        guess: guess.to_owned(),
+        outcome: GuessOutcome::Graded(grade.clone()),
+    });
+    let solved = grade.iter().flatten().all(|x| *x == Grade::Correct);
+    let guesses_remaining = if solved {
+        0
+    } else {
+        (MAX_GUESSES + 1).saturating_sub(state.num_guesses)
+    };
+    let finished = guesses_remaining == 0;
 
-        let grade = state
-            .words
+    let reveal = finished.then(|| {
+        let answer = state.seed.name();
+        let board: Vec<_> = state
+            .history
             .iter()
-            .zip(words)
-            .map(|(expected, word)| {
-                if expected != word && !full_state.word_list.contains(word) {
-                    return Err(AppError::UnknownWord(word.to_owned()));
-                }
-                grade(expected, word).map_err(AppError::GradingError)
+            .filter_map(|record| match &record.outcome {
+                GuessOutcome::Graded(grade) => Some(grade.clone()),
+                GuessOutcome::Rejected(_) => None,
             })
-            .collect::<Result<Vec<_>, _>>()?;
+            .collect();
+        Reveal {
+            answer: answer.to_owned(),
+            image: state.seed.image().map(ToOwned::to_owned),
+            release_year: None,
+            share_checksum: (!state.practice)
+                .then(|| share_checksum(&full_state.config.jwt_key, answer, &board)),
+        }
+    });
+
+    Ok(GuessResult {
+        grade,
+        normalized_guess,
+        elapsed_ms: state.started_at.elapsed().as_millis() as u64,
+        guesses_remaining,
+        reveal,
+    })
+}
+
+/// Grade `guess` against the answer of the game `state`, or against the alternate title it is
+/// closest to.
+fn best_grade(
+    full_state: &SharedState,
+    state: &SessionState,
+    guess: &[String],
+) -> Result<Vec<Vec<Grade>>, AppError> {
+    let words: Vec<_> = guess.iter().map(String::as_str).collect();
+    std::iter::once(&state.words)
+        .chain(&state.alternates)
+        .map(|target| grade_target(target, &words, &full_state.word_list))
+        .reduce(|best, graded| match (&best, &graded) {
+            (Ok(b), Ok(g)) if num_correct(g) > num_correct(b) => graded,
+            (Err(_), Ok(_)) => graded,
+            _ => best,
+        })
+        .expect("the answer itself is always a target")
+}
 
-        state.num_guesses += 1;
-        *should_delete =
-            state.num_guesses > MAX_GUESSES || grade.iter().flatten().all(|x| *x == Grade::Correct);
+/// The grades a guess would get, without making it.
+#[derive(Serialize, TS)]
+#[ts(export)]
+struct PreviewResult {
+    grade: Vec<Vec<Grade>>,
+    /// The words that were graded, after lowercasing and dropping accents and punctuation.
+    /// Apostrophes are kept if the game was created with them.
+    normalized_guess: Vec<String>,
+}
 
-        Ok(GuessResult { grade })
+/// Grade a guess without recording it. Only practice games allow this, since it would let
+/// players try guesses for free.
+async fn preview_guess(
+    State(full_state): State<SharedState>,
+    Json(guess): Json<GuessArgs>,
+) -> Result<Json<PreviewResult>, AppError> {
+    let state = full_state
+        .sessions
+        .get(guess.id)
+        .await?
+        .ok_or(AppError::NoSession)?;
+    if !state.practice {
+        return Err(AppError::NotPractice);
     }
+    let normalized_guess = state.sanitize.words(&guess.guess);
+    let grade = best_grade(&full_state, &state, &normalized_guess)?;
+    Ok(Json(PreviewResult {
+        grade,
+        normalized_guess,
+    }))
+}
+
+#[derive(Deserialize, TS)]
+#[ts(export)]
+struct VerifyShareArgs {
+    answer: String,
+    /// The grades of every guess, in order.
+    grades: Vec<Vec<Vec<Grade>>>,
+    checksum: String,
+}
+
+#[derive(Serialize, TS)]
+#[ts(export)]
+struct VerifyShareResult {
+    valid: bool,
+}
 
-    let mut should_delete = false;
-    let id = guess.id;
-    let ret = inner(&full_state, guess, &mut should_delete)?;
+/// Check that a shared board is the one a game on this server ended with, by its
+/// [`Reveal::share_checksum`].
+async fn verify_share(
+    State(state): State<SharedState>,
+    Json(args): Json<VerifyShareArgs>,
+) -> Json<VerifyShareResult> {
+    let valid = share_checksum_matches(
+        &state.config.jwt_key,
+        &args.answer,
+        &args.grades,
+        &args.checksum,
+    );
+    Json(VerifyShareResult { valid })
+}
 
-    if should_delete {
-        full_state.mutable.write().unwrap().db.remove(&id);
+/// Grade the `guess` word by word against the words of `target`.
+fn grade_target(
+    target: &[String],
+    guess: &[&str],
+    word_list: &WordList,
+) -> Result<Vec<Vec<Grade>>, AppError> {
+    if target.len() != guess.len() {
+        return Err(AppError::GradingError(GradingError::WrongNumberOfWords(
+            target.len(),
+            guess.len(),
+        )));
     }
 
-    Ok(Json(ret))
+    target
+        .iter()
+        .zip(guess)
+        .map(|(expected, &word)| {
+            if expected != word && !word_list.contains(word) {
+                return Err(AppError::UnknownWord(word.to_owned()));
+            }
+            grade(expected, word).map_err(AppError::GradingError)
+        })
+        .collect()
 }
 
-async fn root() -> &'static str {
-    "Hello, World!"
+fn num_correct(grade: &[Vec<Grade>]) -> usize {
+    grade
+        .iter()
+        .flatten()
+        .filter(|x| **x == Grade::Correct)
+        .count()
+}
+
+/// Keep the outcome of a finished game for [`album_stats`]. Failing to do so is only logged, as
+/// the player is owed their reveal either way.
+async fn record_completion(state: &SharedState, game: &SessionState, solved: bool) {
+    let key = game.seed.key();
+    let answer = game.seed.name();
+    let guesses = game.num_guesses as i64;
+    let res = sqlx::query!(
+        "INSERT INTO completed_game
+            (answer_key, answer, solved, guesses, completed_at)
+        VALUES (
+            ?, ?, ?, ?, unixepoch()
+        );
+        ",
+        key,
+        answer,
+        solved,
+        guesses
+    )
+    .execute(&state.pool)
+    .await;
+    if let Err(e) = res {
+        log::warn!("could not record the completion of {key}: {e}");
+    }
+}
+
+/// The year the album a game was drawn from came out, for the reveal.
+///
+/// The reveal is still sent when Last.fm does not know the year, cannot be reached or takes
+/// longer than [`RELEASE_YEAR_TIMEOUT`]. Answers are cached, failures are asked again.
+async fn release_year(state: &SharedState, seed: &Seed) -> Option<i32> {
+    let Seed::Album(album) = seed else {
+        return None;
+    };
+    if !state.config.reveal_release_year {
+        return None;
+    }
+    let key = (album.artist.name.clone(), album.name.clone());
+    if let Some(year) = state.release_years.get(&key) {
+        return year;
+    }
+    let request = state
+        .lastfm
+        .album_info(&album.artist.name, &album.name)
+        .send();
+    let info = tokio::time::timeout(
+        RELEASE_YEAR_TIMEOUT.min(state.config.lastfm_timeout),
+        request,
+    )
+    .await
+    .map_err(|_| AppError::Timeout)
+    .and_then(|info| info.map_err(AppError::LastFm));
+    match info {
+        Ok(info) => {
+            let year = info.release_year();
+            state.release_years.insert(key, year);
+            year
+        }
+        Err(e) => {
+            log::warn!("could not look up when {} was released: {e}", album.name);
+            None
+        }
+    }
+}
+
+/// Like [`guess`], but also lists the grades tile by tile so that clients can animate the reveal
+/// across word boundaries.
+async fn guess_tiles(
+    state: State<SharedState>,
+    guess: Json<GuessArgs>,
+) -> Result<Json<TiledGuessResult>, AppError> {
+    let Json(result) = self::guess(state, guess).await?;
+    let tiles = tiles(&result.grade);
+    Ok(Json(TiledGuessResult { result, tiles }))
+}
+
+#[derive(Deserialize)]
+struct RefreshQuery {
+    user: String,
+}
+
+/// Forget the cached Last.fm data of a user, so that their next game draws from fresh favorites.
+/// Every user can refresh once per [`REFRESH_COOLDOWN`], so that the cache cannot be defeated.
+async fn refresh(
+    State(state): State<SharedState>,
+    Query(query): Query<RefreshQuery>,
+) -> Result<StatusCode, AppError> {
+    {
+        let mut mutable = state.mutable.write().unwrap();
+        mutable
+            .refreshed
+            .retain(|_, refreshed| refreshed.elapsed() < REFRESH_COOLDOWN);
+        if mutable.refreshed.contains_key(&query.user) {
+            return Err(AppError::RefreshTooSoon);
+        }
+        mutable.refreshed.insert(query.user.clone(), Instant::now());
+    }
+    state.prefetched.evict(|(user, _)| *user == query.user);
+    log::info!("refreshing data of {}", query.user);
+    state.lastfm.forget_user(&query.user);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct HistoryQuery {
+    id: Uuid,
+}
+
+/// Every guess made so far in a running game, including rejected ones.
+async fn history(
+    State(state): State<SharedState>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<Vec<GuessRecord>>, AppError> {
+    let session = state.sessions.get(query.id).await?;
+    Ok(Json(session.ok_or(AppError::NoSession)?.history))
+}
+
+async fn root() -> &'static str {
+    "Hello, World!"
+}
+
+#[derive(Serialize, TS)]
+#[ts(export)]
+struct VersionInfo {
+    version: &'static str,
+    git_sha: &'static str,
+    lastfm_api_version: &'static str,
+}
+
+async fn version() -> Json<VersionInfo> {
+    Json(VersionInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        git_sha: env!("GIT_SHA"),
+        lastfm_api_version: lastfm::API_VERSION,
+    })
+}
+
+/// Check for the configured admin token in the `Authorization: Bearer` header.
+fn require_admin(config: &Config, headers: &HeaderMap) -> Result<(), AppError> {
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match (&config.admin_token, token) {
+        (Some(expected), Some(token)) if expected == token => Ok(()),
+        _ => Err(AppError::NotAdmin),
+    }
+}
+
+#[derive(Deserialize)]
+struct AlbumStatsQuery {
+    /// How many albums to list at either end.
+    #[serde(default = "default_album_stats_limit")]
+    limit: usize,
+}
+
+fn default_album_stats_limit() -> usize {
+    10
+}
+
+/// How an album fared as an answer.
+#[derive(Serialize, TS, Debug, Clone, PartialEq)]
+#[ts(export)]
+struct AlbumStat {
+    key: String,
+    answer: String,
+    /// The number of finished games.
+    #[ts(type = "number")]
+    games: i64,
+    /// The share of games that were solved, from 0 to 1.
+    solve_rate: f64,
+    /// The average number of guesses of the solved games, unset if none was.
+    average_guesses: Option<f64>,
+}
+
+#[derive(Serialize, TS)]
+#[ts(export)]
+struct AlbumStats {
+    /// Hardest first.
+    hardest: Vec<AlbumStat>,
+    /// Easiest first.
+    easiest: Vec<AlbumStat>,
+}
+
+/// The albums that were hardest and easiest to guess in finished games. Albums are ranked by how
+/// often they were solved, then by how many guesses that took.
+async fn album_stats(
+    headers: HeaderMap,
+    Query(query): Query<AlbumStatsQuery>,
+    State(state): State<SharedState>,
+) -> Result<Json<AlbumStats>, AppError> {
+    require_admin(&state.config, &headers)?;
+    let hardest = sqlx::query_as!(
+        AlbumStat,
+        r#"SELECT
+            answer_key AS "key!",
+            max(answer) AS "answer!: String",
+            count(*) AS "games!: i64",
+            avg(solved) AS "solve_rate!: f64",
+            avg(CASE WHEN solved THEN guesses END) AS "average_guesses: f64"
+        FROM completed_game
+        WHERE answer_key LIKE 'album/%'
+        GROUP BY answer_key
+        ORDER BY 4 ASC, 5 DESC, 1"#
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(AppError::internal)?;
+
+    let easiest = hardest.iter().rev().take(query.limit).cloned().collect();
+    let mut hardest = hardest;
+    hardest.truncate(query.limit);
+    Ok(Json(AlbumStats { hardest, easiest }))
+}
+
+#[derive(Deserialize)]
+struct ExportQuery {
+    user: String,
+    #[serde(default)]
+    period: Option<Period>,
+}
+
+#[derive(Serialize, TS)]
+#[ts(export)]
+struct ExportedAlbum {
+    artist: String,
+    name: String,
+    /// The words the album would be played as.
+    words: Vec<String>,
+}
+
+/// All of a user's top albums as a JSON array of [`ExportedAlbum`]s, for building a word bank.
+///
+/// The array is streamed as the pages come in from Last.fm, so large libraries neither have to
+/// fit in memory nor wait for the last page before the first bytes go out.
+async fn export(
+    headers: HeaderMap,
+    Query(query): Query<ExportQuery>,
+    State(state): State<SharedState>,
+) -> Result<Response, AppError> {
+    require_admin(&state.config, &headers)?;
+    log::info!("exporting albums of {}", query.user);
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<String, lastfm::Error>>(4);
+    tokio::spawn(async move {
+        let period = query.period.unwrap_or(Period::Overall);
+        let mut pages = std::pin::pin!(state.lastfm.top_albums_pages(&query.user, period));
+        let mut separator = "[";
+        while let Some(page) = pages.next().await {
+            let page = match page {
+                Ok(page) => page,
+                Err(e) => {
+                    log::error!("export of {} failed: {e}", query.user);
+                    let _ = tx.send(Err(e)).await;
+                    return;
+                }
+            };
+            for album in page.albums {
+                let album = ExportedAlbum {
+                    words: Sanitize::default().words(&album.name),
+                    artist: album.artist.name,
+                    name: album.name,
+                };
+                let json = serde_json::to_string(&album).expect("can serialize");
+                if tx.send(Ok(format!("{separator}{json}"))).await.is_err() {
+                    // the client went away
+                    return;
+                }
+                separator = ",";
+            }
+        }
+        let end = if separator == "[" { "[]" } else { "]" };
+        let _ = tx.send(Ok(end.to_owned())).await;
+    });
+
+    let chunks = stream::unfold(rx, |mut rx| async move {
+        let chunk = rx.recv().await?;
+        Some((chunk, rx))
+    });
+    Ok((
+        [(CONTENT_TYPE, "application/json")],
+        Body::from_stream(chunks),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sessions::{InMemorySessions, SessionBackend};
+
+    use futures_util::FutureExt;
+    use hmac::Mac;
+    use lastfm::api_types::ShortArtist;
+    use lastfm::test_util::{top_albums, top_albums_pages};
+    use pretty_assertions::assert_eq;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{header, method, query_param},
+    };
+
+    fn test_state(lastfm: &MockServer) -> SharedState {
+        let config = Config {
+            lastfm_apikey: "apikey".into(),
+            lastfm_shared_secret: "shared_secret".into(),
+            auth_callback_url: "http://localhost/callback".into(),
+            jwt_key: Hmac::new_from_slice(b"secret").unwrap(),
+            database_url: "sqlite::memory:".into(),
+            lastfm_timeout: Duration::from_millis(100),
+            record_rejected_guesses: true,
+            max_answer_len: None,
+            avoid_recent_answers: 0,
+            admin_token: Some("admin".into()),
+            seed_sources: vec![
+                SeedSource::TopAlbums,
+                SeedSource::RecentTracks,
+                SeedSource::Charts,
+            ],
+            puzzle_epoch: time::macros::date!(2025 - 01 - 01),
+            default_period: Period::Overall,
+            remember_settings: false,
+            reveal_release_year: false,
+            session_backend: SessionBackend::InMemory,
+            warmup_users: Vec::new(),
+        };
+        let lastfm = lastfm::Client::builder("shared_secret".into(), "apikey".into())
+            .base_url(lastfm.uri())
+            .build()
+            .unwrap();
+        // a single connection, as every connection would get its own in-memory database
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_lazy("sqlite::memory:")
+            .unwrap();
+        SharedState {
+            mutable: Default::default(),
+            prefetched: Arc::new(TtlCache::new(PREFETCH_TTL)),
+            release_years: Arc::new(TtlCache::new(RELEASE_YEAR_TTL)),
+            sessions: Arc::new(Sessions::new(config.session_backend, pool.clone())),
+            config: Arc::new(config),
+            pool,
+            lastfm: Arc::new(lastfm),
+            rng: DrawRng::default(),
+            word_list: WordList::new("./wordlist.txt"),
+        }
+    }
+
+    fn album(artist: &str, name: &str) -> Album {
+        Album {
+            rank: Some(1),
+            name: name.into(),
+            playcount: 1,
+            mbid: String::new(),
+            url: "https://www.last.fm/music/x".parse().unwrap(),
+            artist: ShortArtist {
+                name: artist.into(),
+                mbid: String::new(),
+                url: "https://www.last.fm/music/x".parse().unwrap(),
+                images: Vec::new(),
+            },
+            images: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn merge_periods_dedups_albums() {
+        let overall = vec![
+            album("Dream Theater", "Images and Words"),
+            album("Haken", "Vector"),
+        ];
+        let month = vec![
+            album("Haken", "Vector"),
+            album("Leprous", "Pitfalls"),
+            album("Haken", "Virus"),
+        ];
+
+        let merged: Vec<_> = merge_dedup([overall, month], album_key)
+            .into_iter()
+            .map(|album| album.name)
+            .collect();
+        assert_eq!(merged, ["Images and Words", "Vector", "Pitfalls", "Virus"]);
+    }
+
+    #[test]
+    fn album_key_prefers_mbid() {
+        let mut original = album("Haken", "Vector");
+        original.mbid = "0b8f1a4e".into();
+        let mut reissue = album("Haken", "Vector (Deluxe Edition)");
+        reissue.mbid = "0b8f1a4e".into();
+        assert_eq!(album_key(&original), album_key(&reissue));
+
+        let mut other = album("Haken", "Vector");
+        other.mbid = "5c2d9e71".into();
+        assert_ne!(album_key(&original), album_key(&other));
+    }
+
+    #[test]
+    fn album_key_falls_back_to_normalized_names() {
+        assert_eq!(
+            album_key(&album("Dream Theater", "Images and Words")),
+            album_key(&album(" dream  theater", "Images And Words ")),
+        );
+        assert_ne!(
+            album_key(&album("Haken", "Vector")),
+            album_key(&album("Haken", "Virus")),
+        );
+    }
+
+    #[test]
+    fn echoed_period_mismatch_warns() {
+        assert!(check_echoed_period(
+            "RJ",
+            Period::SevenDay,
+            Some(Period::SevenDay)
+        ));
+        assert!(check_echoed_period("RJ", Period::SevenDay, None));
+        assert!(!check_echoed_period(
+            "RJ",
+            Period::SevenDay,
+            Some(Period::Overall)
+        ));
+    }
+
+    #[test]
+    fn parse_periods() {
+        let query = |periods: Option<&str>| NewGameQuery {
+            periods: periods.map(ToOwned::to_owned),
+            ..Default::default()
+        };
+        assert_eq!(
+            query(None).periods(Period::Overall).unwrap(),
+            [Period::Overall]
+        );
+        assert_eq!(
+            query(None).periods(Period::ThreeMonth).unwrap(),
+            [Period::ThreeMonth]
+        );
+        assert_eq!(
+            query(Some("overall,1month"))
+                .periods(Period::Overall)
+                .unwrap(),
+            [Period::Overall, Period::OneMonth]
+        );
+        assert!(
+            query(Some("overall,never"))
+                .periods(Period::Overall)
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn newgame_times_out_on_slow_lastfm() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("method", "user.getTopArtists"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(
+                        r#"<lfm status="ok"><topartists user="RJ"></topartists></lfm>"#,
+                    )
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .mount(&server)
+            .await;
+
+        let query = NewGameQuery {
+            user: Some("RJ".into()),
+            ..Default::default()
+        };
+        let res = newgame(
+            CookieJar::new(),
+            HeaderMap::new(),
+            Query(query),
+            State(test_state(&server)),
+        )
+        .await;
+        assert!(matches!(res, Err(AppError::Timeout)));
+    }
+
+    const TOP_ARTISTS: &str = r#"<lfm status="ok">
+<topartists user="RJ" type="overall">
+  <artist rank="1">
+    <name>Dream Theater</name>
+    <playcount>1337</playcount>
+    <mbid>28503ab7-8bf2-4666-a7bd-2644bfc7cb1d</mbid>
+    <url>http://www.last.fm/music/Dream+Theater</url>
+    <streamable>1</streamable>
+    <image size="small">...</image>
+  </artist>
+  <artist rank="2">
+    <name>Haken</name>
+    <playcount>420</playcount>
+    <mbid></mbid>
+    <url>http://www.last.fm/music/Haken</url>
+    <streamable>1</streamable>
+    <image size="small">...</image>
+  </artist>
+</topartists>
+</lfm>"#;
+
+    #[tokio::test]
+    async fn newgame_is_idempotent_per_key() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("method", "user.getTopArtists"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(TOP_ARTISTS))
+            .expect(3)
+            .mount(&server)
+            .await;
+        let state = test_state(&server);
+        sqlx::migrate!().run(&state.pool).await.unwrap();
+
+        let start = |user: &str, key: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("idempotency-key", HeaderValue::from_static(key));
+            let query = NewGameQuery {
+                user: Some(user.into()),
+                ..Default::default()
+            };
+            newgame(
+                CookieJar::new(),
+                headers,
+                Query(query),
+                State(state.clone()),
+            )
+        };
+
+        let first = start("RJ", "retry-me").await.unwrap().0;
+        let retried = start("RJ", "retry-me").await.unwrap().0;
+        let other = start("RJ", "something-else").await.unwrap().0;
+        // keys are only replayed for the user they were sent for
+        let other_user = start("hydehsmf", "retry-me").await.unwrap().0;
+        assert_eq!(first.id, retried.id);
+        assert_ne!(first.id, other.id);
+        assert_ne!(first.id, other_user.id);
+        assert_eq!(in_memory(&state).games.read().unwrap().len(), 3);
+
+        // the keys are kept in the database, not the memory of the server
+        let restarted = SharedState {
+            mutable: Default::default(),
+            ..state.clone()
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert("idempotency-key", HeaderValue::from_static("retry-me"));
+        let query = NewGameQuery {
+            user: Some("RJ".into()),
+            ..Default::default()
+        };
+        let Json(replayed) = newgame(CookieJar::new(), headers, Query(query), State(restarted))
+            .await
+            .unwrap();
+        assert_eq!(first.id, replayed.id);
+
+        // nor for another endpoint
+        Mock::given(method("GET"))
+            .and(query_param("method", "user.getTopAlbums"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(top_albums(&["Vector"])))
+            .mount(&server)
+            .await;
+        let mut headers = HeaderMap::new();
+        headers.insert("idempotency-key", HeaderValue::from_static("retry-me"));
+        let query = NewGameQuery {
+            user: Some("RJ".into()),
+            ..Default::default()
+        };
+        let Json(album_game) = newgame_album(
+            CookieJar::new(),
+            headers,
+            Query(query),
+            State(state.clone()),
+        )
+        .await
+        .unwrap();
+        assert_ne!(first.id, album_game.id);
+    }
+
+    #[tokio::test]
+    async fn seeded_rng_draws_deterministically() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("method", "user.getTopArtists"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(TOP_ARTISTS))
+            .mount(&server)
+            .await;
+
+        let answers = |seed| {
+            let state = SharedState {
+                rng: DrawRng::seeded(seed),
+                ..test_state(&server)
+            };
+            async move {
+                let mut answers = Vec::new();
+                for _ in 0..8 {
+                    let query = NewGameQuery {
+                        user: Some("RJ".into()),
+                        ..Default::default()
+                    };
+                    let Json(game) = newgame(
+                        CookieJar::new(),
+                        HeaderMap::new(),
+                        Query(query),
+                        State(state.clone()),
+                    )
+                    .await
+                    .unwrap();
+                    answers.push(session(&state, game.id).await.seed.name().to_owned());
+                }
+                answers
+            }
+        };
+
+        let first = answers(7).await;
+        assert_eq!(first, answers(7).await);
+        assert_eq!(
+            first,
+            [
+                "Dream Theater",
+                "Dream Theater",
+                "Dream Theater",
+                "Haken",
+                "Dream Theater",
+                "Haken",
+                "Dream Theater",
+                "Dream Theater"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn version_reports_cargo_version() {
+        let Json(info) = version().await;
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(info.lastfm_api_version, "2.0");
+        assert!(!info.git_sha.is_empty());
+    }
+
+    async fn start_session(state: &SharedState, words: &[&str]) -> Uuid {
+        let seed = album("Test", &words.join(" ")).into();
+        let words = words.iter().map(|&word| word.to_owned()).collect();
+        start_game(state, None, &NewGameQuery::default(), seed, None, 1, words)
+            .await
+            .unwrap()
+            .id
+    }
+
+    /// The games of a state using the in-memory backend.
+    fn in_memory(state: &SharedState) -> &InMemorySessions {
+        let Sessions::InMemory(sessions) = &*state.sessions else {
+            panic!("expected the in-memory backend");
+        };
+        sessions
+    }
+
+    /// The game `id` as it is stored right now.
+    async fn session(state: &SharedState, id: Uuid) -> SessionState {
+        state.sessions.get(id).await.unwrap().unwrap()
+    }
+
+    async fn make_guess(
+        state: &SharedState,
+        id: Uuid,
+        guess: &str,
+    ) -> Result<GuessResult, AppError> {
+        let args = GuessArgs {
+            id,
+            guess: guess.into(),
+        };
+        super::guess(State(state.clone()), Json(args))
+            .await
+            .map(|Json(res)| res)
+    }
+
+    #[tokio::test]
+    async fn rejected_guess_is_recorded_without_counting() {
+        let server = MockServer::start().await;
+        let state = test_state(&server);
+        let id = start_session(&state, &["vector"]).await;
+
+        let res = make_guess(&state, id, "horse").await;
+        assert!(matches!(
+            res,
+            Err(AppError::GradingError(GradingError::WrongLength(6, 5)))
+        ));
+        make_guess(&state, id, "sector").await.unwrap();
+
+        let Json(history) = history(State(state.clone()), Query(HistoryQuery { id }))
+            .await
+            .unwrap();
+        assert!(matches!(
+            &history[..],
+            [
+                GuessRecord {
+                    outcome: GuessOutcome::Rejected(GradingError::WrongLength(6, 5)),
+                    ..
+                },
+                GuessRecord {
+                    outcome: GuessOutcome::Graded(_),
+                    ..
+                },
+            ]
+        ));
+        assert_eq!(history[0].guess, "horse");
+
+        assert_eq!(session(&state, id).await.num_guesses, 1);
+    }
+
+    #[tokio::test]
+    async fn preview_leaves_the_game_alone() {
+        let state = test_state(&MockServer::start().await);
+        let query = NewGameQuery {
+            practice: true,
+            ..Default::default()
+        };
+        let seed = album("Test", "vector").into();
+        let id = start_game(&state, None, &query, seed, None, 1, vec!["vector".into()])
+            .await
+            .unwrap()
+            .id;
+        let preview = |guess: &str| {
+            let args = GuessArgs {
+                id,
+                guess: guess.into(),
+            };
+            preview_guess(State(state.clone()), Json(args))
+        };
+
+        let Json(res) = preview("sector").await.unwrap();
+        assert_eq!(res.normalized_guess, ["sector"]);
+        assert_eq!(res.grade[0][1..], [Grade::Correct; 5]);
+        assert_eq!(session(&state, id).await.num_guesses, 0);
+        assert!(session(&state, id).await.history.is_empty());
+
+        make_guess(&state, id, "sector").await.unwrap();
+        assert_eq!(session(&state, id).await.num_guesses, 1);
+    }
+
+    #[tokio::test]
+    async fn preview_is_refused_outside_practice() {
+        let state = test_state(&MockServer::start().await);
+        let id = start_session(&state, &["vector"]).await;
+
+        let args = GuessArgs {
+            id,
+            guess: "sector".into(),
+        };
+        let res = preview_guess(State(state.clone()), Json(args)).await;
+        assert!(matches!(res, Err(AppError::NotPractice)));
+    }
+
+    #[tokio::test]
+    async fn apostrophes_are_dropped_from_guesses_like_from_the_answer() {
+        let state = test_state(&MockServer::start().await);
+        let words = Sanitize::default().words("Caligula's Horse");
+        let words: Vec<_> = words.iter().map(String::as_str).collect();
+        let id = start_session(&state, &words).await;
+
+        let res = make_guess(&state, id, "Caligula's Horse").await.unwrap();
+        assert_eq!(res.normalized_guess, ["caligulas", "horse"]);
+        assert!(res.reveal.is_some());
+    }
+
+    #[tokio::test]
+    async fn tampered_share_fails_verification() {
+        let state = test_state(&MockServer::start().await);
+        let id = start_session(&state, &["vector"]).await;
+        let first = make_guess(&state, id, "sector").await.unwrap();
+        let last = make_guess(&state, id, "vector").await.unwrap();
+        let checksum = last.reveal.unwrap().share_checksum.unwrap();
+
+        let verify = |grades: Vec<Vec<Vec<Grade>>>| {
+            let args = VerifyShareArgs {
+                answer: "vector".into(),
+                grades,
+                checksum: checksum.clone(),
+            };
+            verify_share(State(state.clone()), Json(args))
+        };
+        let genuine = vec![first.grade.clone(), last.grade.clone()];
+        assert!(verify(genuine).await.valid);
+        // claiming the game was solved in one guess
+        assert!(!verify(vec![last.grade.clone()]).await.valid);
+        let mut tampered = vec![first.grade, last.grade];
+        tampered[0][0][0] = Grade::Correct;
+        assert!(!verify(tampered).await.valid);
+    }
+
+    #[tokio::test]
+    async fn guesses_remaining_counts_down_to_zero() {
+        let state = test_state(&MockServer::start().await);
+        let id = start_session(&state, &["vector"]).await;
+
+        let res = make_guess(&state, id, "horse").await;
+        assert!(res.is_err());
+        for remaining in (1..=MAX_GUESSES).rev() {
+            let res = make_guess(&state, id, "sector").await.unwrap();
+            assert_eq!(res.guesses_remaining, remaining);
+            assert!(res.reveal.is_none());
+        }
+        let res = make_guess(&state, id, "hector").await.unwrap();
+        assert_eq!(res.guesses_remaining, 0);
+        assert!(res.reveal.is_some());
+    }
+
+    #[tokio::test]
+    async fn solving_leaves_no_guesses_remaining() {
+        let state = test_state(&MockServer::start().await);
+        let id = start_session(&state, &["vector"]).await;
+
+        let res = make_guess(&state, id, "vector").await.unwrap();
+        assert_eq!(res.guesses_remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn signin_reports_failed_session_store() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("method", "auth.getSession"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<lfm status="ok">
+                    <session>
+                        <name>RJ</name>
+                        <key>d580d57f32848f5dcf574d1ce18d78b2</key>
+                        <subscriber>0</subscriber>
+                    </session>
+                </lfm>"#,
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // the in-memory test database has no tables, so storing the session fails
+        let query = SigninQuery {
+            token: "token".into(),
+        };
+        let res = signin(State(test_state(&server)), Query(query), CookieJar::new()).await;
+        let Err(err) = res else {
+            panic!("signin should fail");
+        };
+        assert!(matches!(err, AppError::SessionStore(_)));
+        let resp = err.into_response();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn maintenance_is_unavailable() {
+        let resp = AppError::LastFm(lastfm::Error::Maintenance).into_response();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn min_words_draws_multi_word_answers() {
+        let state = test_state(&MockServer::start().await);
+        let query = NewGameQuery {
+            min_words: Some(2),
+            ..Default::default()
+        };
+        let pool = || {
+            vec![
+                album("Haken", "Vector"),
+                album("Dream Theater", "Images and Words"),
+                album("Leprous", "Pitfalls"),
+            ]
+        };
+        for _ in 0..20 {
+            let (album, words) = query
+                .draw(&state.config, pool(), &[], &mut rand::rng())
+                .unwrap();
+            assert_eq!(album.name, "Images and Words");
+            assert_eq!(words, ["images", "and", "words"]);
+        }
+
+        let single = vec![album("Haken", "Vector"), album("Leprous", "Pitfalls")];
+        let res = query.draw(&state.config, single, &[], &mut rand::rng());
+        assert!(matches!(res, Err(AppError::TooFewWords(2))));
+    }
+
+    #[tokio::test]
+    async fn guess_is_echoed_normalized() {
+        let state = test_state(&MockServer::start().await);
+        let id = start_session(&state, &["cafe"]).await;
+
+        let res = make_guess(&state, id, "CAFÉ").await.unwrap();
+        assert_eq!(res.normalized_guess, ["cafe"]);
+        assert_eq!(res.grade, [[Grade::Correct; 4]]);
+    }
+
+    #[tokio::test]
+    async fn concurrent_guess_conflicts() {
+        let state = test_state(&MockServer::start().await);
+        let id = start_session(&state, &["vector"]).await;
+
+        let in_flight = {
+            let games = in_memory(&state).games.read().unwrap();
+            // stands in for the first of two simultaneous guesses, holding the session while it
+            // is graded
+            let _first = games[&id].lock().unwrap();
+            make_guess(&state, id, "sector").now_or_never().unwrap()
+        };
+        let Err(err) = in_flight else {
+            panic!("second guess should be turned away");
+        };
+        assert!(matches!(err, AppError::GuessInFlight));
+        assert_eq!(err.into_response().status(), StatusCode::CONFLICT);
+
+        make_guess(&state, id, "sector").await.unwrap();
+        assert_eq!(session(&state, id).await.num_guesses, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn game_removed_during_update_is_gone() {
+        let mut state = test_state(&MockServer::start().await);
+        sqlx::migrate!().run(&state.pool).await.unwrap();
+        state.sessions = Arc::new(Sessions::new(SessionBackend::Sqlite, state.pool.clone()));
+        let id = start_session(&state, &["vector"]).await;
+
+        let res = state
+            .sessions
+            .update(id, |_| {
+                // stands in for a guess that finished the game in the meantime
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Handle::current().block_on(state.sessions.remove(id))
+                })
+                .unwrap();
+                Ok(())
+            })
+            .await;
+        assert!(matches!(res, Err(AppError::NoSession)));
+    }
+
+    #[tokio::test]
+    async fn game_flow_on_every_backend() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("method", "user.getTopAlbums"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(top_albums(&["Vector"])))
+            .mount(&server)
+            .await;
+
+        for backend in [SessionBackend::InMemory, SessionBackend::Sqlite] {
+            let mut state = test_state(&server);
+            sqlx::migrate!().run(&state.pool).await.unwrap();
+            state.sessions = Arc::new(Sessions::new(backend, state.pool.clone()));
+            let query = NewGameQuery {
+                user: Some("RJ".into()),
+                ..Default::default()
+            };
+            let Json(game) = newgame_album(
+                CookieJar::new(),
+                HeaderMap::new(),
+                Query(query),
+                State(state.clone()),
+            )
+            .await
+            .unwrap();
+            let history =
+                || super::history(State(state.clone()), Query(HistoryQuery { id: game.id }));
+
+            let res = make_guess(&state, game.id, "vectors").await;
+            assert!(matches!(res, Err(AppError::GradingError(_))), "{backend:?}");
+            let res = make_guess(&state, game.id, "sector").await.unwrap();
+            assert!(res.reveal.is_none(), "{backend:?}");
+            let Json(records) = history().await.unwrap();
+            assert_eq!(records.len(), 2, "{backend:?}");
+            assert_eq!(session(&state, game.id).await.num_guesses, 1);
+
+            let res = make_guess(&state, game.id, "vector").await.unwrap();
+            assert_eq!(res.reveal.unwrap().answer, "Vector", "{backend:?}");
+            assert!(matches!(history().await, Err(AppError::NoSession)));
+            let res = make_guess(&state, game.id, "vector").await;
+            assert!(matches!(res, Err(AppError::NoSession)), "{backend:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn empty_words_are_not_graded() {
+        let state = test_state(&MockServer::start().await);
+        let id = start_session(&state, &["", "vector", ""]).await;
+        let res = make_guess(&state, id, "vector").await.unwrap();
+        assert_eq!(res.grade, [[Grade::Correct; 6]]);
+    }
+
+    #[tokio::test]
+    async fn spacing_does_not_change_word_count() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("method", "user.getTopAlbums"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(top_albums(&[" The  Mountain "])),
+            )
+            .mount(&server)
+            .await;
+        let state = test_state(&server);
+        let query = NewGameQuery {
+            user: Some("RJ".into()),
+            ..Default::default()
+        };
+
+        let Json(game) = newgame_album(
+            CookieJar::new(),
+            HeaderMap::new(),
+            Query(query),
+            State(state.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(game.len, [3, 8]);
+        let res = make_guess(&state, game.id, "the  mountain").await.unwrap();
+        assert_eq!(
+            res.grade,
+            [[Grade::Correct; 3].to_vec(), [Grade::Correct; 8].to_vec()]
+        );
+    }
+
+    #[tokio::test]
+    async fn forgiving_game_accepts_alternate_title() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("method", "user.getTopAlbums"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(top_albums(&["Sundowning (Deluxe)"])),
+            )
+            .mount(&server)
+            .await;
+        let state = test_state(&server);
+        let query = NewGameQuery {
+            user: Some("RJ".into()),
+            forgiving: true,
+            ..Default::default()
+        };
+
+        let Json(game) = newgame_album(
+            CookieJar::new(),
+            HeaderMap::new(),
+            Query(query),
+            State(state.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(game.len, [10, 6]);
+        let res = make_guess(&state, game.id, "sundowning").await.unwrap();
+        assert_eq!(res.grade, [[Grade::Correct; 10]]);
+        assert_eq!(res.reveal.unwrap().answer, "Sundowning (Deluxe)");
+    }
+
+    #[tokio::test]
+    async fn refresh_refetches_top_albums() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(query_param("method", "user.getTopAlbums"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v1\"")
+                    .set_body_string(top_albums(&["Vector"])),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        let state = test_state(&server);
+        let refresh = || {
+            super::refresh(
+                State(state.clone()),
+                Query(RefreshQuery { user: "RJ".into() }),
+            )
+        };
+        let newgame = || {
+            let query = NewGameQuery {
+                user: Some("RJ".into()),
+                ..Default::default()
+            };
+            newgame_album(
+                CookieJar::new(),
+                HeaderMap::new(),
+                Query(query),
+                State(state.clone()),
+            )
+        };
+
+        let Json(_) = newgame().await.unwrap();
+        let Json(_) = newgame().await.unwrap();
+        assert_eq!(refresh().await.unwrap(), StatusCode::NO_CONTENT);
+        let Json(_) = newgame().await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests[2].headers.get("if-none-match").is_none());
+
+        let Err(err) = refresh().await else {
+            panic!("second refresh should be rate limited");
+        };
+        assert_eq!(err.into_response().status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn warmed_up_user_draws_without_lastfm() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("method", "user.getTopAlbums"))
+            .and(query_param("user", "RJ"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(top_albums(&["Vector"])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let mut state = test_state(&server);
+        Arc::get_mut(&mut state.config).unwrap().warmup_users = vec!["RJ".into()];
+
+        warm_up(state.clone()).await;
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+        let query = NewGameQuery {
+            user: Some("RJ".into()),
+            ..Default::default()
+        };
+        let Json(game) = newgame_album(
+            CookieJar::new(),
+            HeaderMap::new(),
+            Query(query),
+            State(state.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(session(&state, game.id).await.words, ["vector"]);
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+        // the prefetched albums stay for later games
+        let query = NewGameQuery {
+            user: Some("RJ".into()),
+            ..Default::default()
+        };
+        let Json(game) = newgame_album(
+            CookieJar::new(),
+            HeaderMap::new(),
+            Query(query),
+            State(state.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(session(&state, game.id).await.words, ["vector"]);
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn tiles_follow_nested_grades() {
+        let state = test_state(&MockServer::start().await);
+        let id = start_session(&state, &["images", "and", "words"]).await;
+
+        let args = GuessArgs {
+            id,
+            guess: "waters and sword".into(),
+        };
+        let Json(res) = guess_tiles(State(state.clone()), Json(args)).await.unwrap();
+        let nested: Vec<_> = res.result.grade.iter().flatten().copied().collect();
+        let flat: Vec<_> = res.tiles.iter().map(|tile| tile.grade).collect();
+        assert_eq!(flat, nested);
+        let positions: Vec<_> = res
+            .tiles
+            .iter()
+            .map(|tile| (tile.word_index, tile.char_index))
+            .collect();
+        assert_eq!(positions[..3], [(0, 0), (0, 1), (0, 2)]);
+        assert_eq!(positions[6..9], [(1, 0), (1, 1), (1, 2)]);
+        assert_eq!(positions[9..], [(2, 0), (2, 1), (2, 2), (2, 3), (2, 4)]);
+    }
+
+    #[tokio::test]
+    async fn guesses_report_elapsed_time() {
+        let server = MockServer::start().await;
+        for backend in [SessionBackend::InMemory, SessionBackend::Sqlite] {
+            let mut state = test_state(&server);
+            sqlx::migrate!().run(&state.pool).await.unwrap();
+            state.sessions = Arc::new(Sessions::new(backend, state.pool.clone()));
+            let id = start_session(&state, &["vector"]).await;
+
+            skip_ahead(Duration::from_millis(1500)).await;
+            let res = make_guess(&state, id, "sector").await.unwrap();
+            assert!((1500..2000).contains(&res.elapsed_ms), "{backend:?}");
+
+            skip_ahead(Duration::from_millis(2000)).await;
+            let res = make_guess(&state, id, "vector").await.unwrap();
+            assert!(res.reveal.is_some(), "{backend:?}");
+            assert!((3500..4000).contains(&res.elapsed_ms), "{backend:?}");
+        }
+    }
+
+    /// Move tokio's clock forward by `duration`. The clock is not left paused, as it would jump
+    /// past the pool's timeout while a SQLite query runs on its worker thread.
+    async fn skip_ahead(duration: Duration) {
+        tokio::time::pause();
+        tokio::time::advance(duration).await;
+        tokio::time::resume();
+    }
+
+    fn album_info(name: &str, wiki: &str) -> String {
+        format!(
+            r#"<lfm status="ok">
+<album>
+  <name>{name}</name>
+  <artist>Test</artist>
+  <url>https://www.last.fm/music/Test/{name}</url>
+  <image size="small"></image>
+  <listeners>1</listeners>
+  <playcount>1</playcount>
+  {wiki}
+</album>
+</lfm>"#
+        )
+    }
+
+    #[tokio::test]
+    async fn reveal_includes_release_year() {
+        let server = MockServer::start().await;
+        let wiki = "<wiki><published>09 Nov 2018, 10:20</published><summary/><content/></wiki>";
+        Mock::given(method("GET"))
+            .and(query_param("method", "album.getInfo"))
+            .and(query_param("album", "vector"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(album_info("vector", wiki)))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(query_param("method", "album.getInfo"))
+            .and(query_param("album", "sector"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(album_info("sector", "")))
+            .mount(&server)
+            .await;
+        let mut state = test_state(&server);
+        Arc::get_mut(&mut state.config).unwrap().reveal_release_year = true;
+
+        let id = start_session(&state, &["vector"]).await;
+        let reveal = make_guess(&state, id, "vector")
+            .await
+            .unwrap()
+            .reveal
+            .unwrap();
+        assert_eq!(reveal.release_year, Some(2018));
+
+        // looked up once per album
+        let id = start_session(&state, &["vector"]).await;
+        let reveal = make_guess(&state, id, "vector")
+            .await
+            .unwrap()
+            .reveal
+            .unwrap();
+        assert_eq!(reveal.release_year, Some(2018));
+
+        let id = start_session(&state, &["sector"]).await;
+        let reveal = make_guess(&state, id, "sector")
+            .await
+            .unwrap()
+            .reveal
+            .unwrap();
+        assert_eq!(reveal.answer, "sector");
+        assert_eq!(reveal.release_year, None);
+    }
+
+    #[tokio::test]
+    async fn reloaded_session_continues() {
+        let state = test_state(&MockServer::start().await);
+        tokio::time::pause();
+        let id = start_session(&state, &["vector"]).await;
+        tokio::time::advance(Duration::from_millis(1500)).await;
+        make_guess(&state, id, "sector").await.unwrap();
+
+        let session = state.sessions.remove(id).await.unwrap().unwrap();
+        let stored = serde_json::to_string(&session).unwrap();
+        let reloaded: SessionState = serde_json::from_str(&stored).unwrap();
+        assert_eq!(reloaded.num_guesses, 1);
+        assert_eq!(reloaded.seed.name(), "vector");
+        state.sessions.insert(id, reloaded).await.unwrap();
+
+        tokio::time::advance(Duration::from_millis(500)).await;
+        let res = make_guess(&state, id, "vector").await.unwrap();
+        assert_eq!(res.elapsed_ms, 2000);
+        assert_eq!(res.reveal.unwrap().answer, "vector");
+
+        // version 3 kept the running time instead of the start
+        let mut v3: serde_json::Value = serde_json::from_str(&stored).unwrap();
+        v3["version"] = 3.into();
+        v3.as_object_mut().unwrap().remove("started_at");
+        v3["elapsed_ms"] = 1500.into();
+        let reloaded: SessionState = serde_json::from_value(v3).unwrap();
+        assert_eq!(reloaded.started_at.elapsed(), Duration::from_millis(1500));
+
+        let mut outdated: serde_json::Value = serde_json::from_str(&stored).unwrap();
+        outdated["version"] = 0.into();
+        assert!(serde_json::from_value::<SessionState>(outdated).is_err());
+    }
+
+    #[tokio::test]
+    async fn export_streams_every_page() {
+        let server = MockServer::start().await;
+        let pages = top_albums_pages(&[&["Vector", "The Mountain"], &["Affinity"]]);
+        for (page, body) in (1..).zip(pages) {
+            Mock::given(method("GET"))
+                .and(query_param("method", "user.getTopAlbums"))
+                .and(query_param("page", page.to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_string(body))
+                .mount(&server)
+                .await;
+        }
+        let state = test_state(&server);
+        let query = || ExportQuery {
+            user: "RJ".into(),
+            period: None,
+        };
+
+        let res = export(HeaderMap::new(), Query(query()), State(state.clone())).await;
+        assert!(matches!(res, Err(AppError::NotAdmin)));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer admin"));
+        let res = export(headers, Query(query()), State(state)).await.unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let albums: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            albums,
+            [
+                serde_json::json!({"artist": "Haken", "name": "Vector", "words": ["vector"]}),
+                serde_json::json!({
+                    "artist": "Haken",
+                    "name": "The Mountain",
+                    "words": ["the", "mountain"]
+                }),
+                serde_json::json!({"artist": "Haken", "name": "Affinity", "words": ["affinity"]}),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn album_stats_rank_by_solve_rate() {
+        let state = test_state(&MockServer::start().await);
+        sqlx::migrate!().run(&state.pool).await.unwrap();
+        // "vector" is solved twice at once, "sector" once in three guesses and once not at all
+        for _ in 0..2 {
+            let id = start_session(&state, &["vector"]).await;
+            make_guess(&state, id, "vector").await.unwrap();
+        }
+        let id = start_session(&state, &["sector"]).await;
+        for guess in ["vector", "hector", "sector"] {
+            make_guess(&state, id, guess).await.unwrap();
+        }
+        let id = start_session(&state, &["sector"]).await;
+        for _ in 0..=MAX_GUESSES {
+            make_guess(&state, id, "vector").await.unwrap();
+        }
+
+        let query = || Query(AlbumStatsQuery { limit: 1 });
+        let res = album_stats(HeaderMap::new(), query(), State(state.clone())).await;
+        assert!(matches!(res, Err(AppError::NotAdmin)));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer admin"));
+        let Json(stats) = album_stats(headers, query(), State(state)).await.unwrap();
+        assert_eq!(
+            stats.hardest,
+            [AlbumStat {
+                key: "album/Test/sector".into(),
+                answer: "sector".into(),
+                games: 2,
+                solve_rate: 0.5,
+                average_guesses: Some(3.0),
+            }]
+        );
+        assert_eq!(
+            stats.easiest,
+            [AlbumStat {
+                key: "album/Test/vector".into(),
+                answer: "vector".into(),
+                games: 2,
+                solve_rate: 1.0,
+                average_guesses: Some(1.0),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn newgame_avoids_recent_answers() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("method", "user.getTopArtists"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(TOP_ARTISTS))
+            .mount(&server)
+            .await;
+        let mut state = test_state(&server);
+        Arc::get_mut(&mut state.config)
+            .unwrap()
+            .avoid_recent_answers = 1;
+        sqlx::migrate!().run(&state.pool).await.unwrap();
+
+        let mut answers = Vec::new();
+        for _ in 0..6 {
+            let query = NewGameQuery {
+                user: Some("RJ".into()),
+                ..Default::default()
+            };
+            let Json(game) = newgame(
+                CookieJar::new(),
+                HeaderMap::new(),
+                Query(query),
+                State(state.clone()),
+            )
+            .await
+            .unwrap();
+            answers.push(session(&state, game.id).await.seed.name().to_owned());
+        }
+        for pair in answers.windows(2) {
+            assert_ne!(pair[0], pair[1], "{answers:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn empty_library_falls_back_to_charts() {
+        let server = MockServer::start().await;
+        for (method_name, body) in [
+            (
+                "user.getTopAlbums",
+                r#"<lfm status="ok"><topalbums user="new" type="overall" page="1" perPage="50" totalPages="0" total="0"></topalbums></lfm>"#,
+            ),
+            (
+                "user.getRecentTracks",
+                r#"<lfm status="ok"><recenttracks user="new" page="1" perPage="50" totalPages="0" total="0"></recenttracks></lfm>"#,
+            ),
+            (
+                "chart.getTopTracks",
+                r#"<lfm status="ok">
+<tracks page="1" perPage="1" totalPages="1" total="1">
+  <track>
+    <name>Blinding Lights</name>
+    <duration>200</duration>
+    <playcount>41267023</playcount>
+    <listeners>2210874</listeners>
+    <mbid></mbid>
+    <url>https://www.last.fm/music/The+Weeknd/_/Blinding+Lights</url>
+    <streamable fulltrack="0">0</streamable>
+    <artist>
+      <name>The Weeknd</name>
+      <mbid></mbid>
+      <url>https://www.last.fm/music/The+Weeknd</url>
+    </artist>
+    <image size="small"></image>
+  </track>
+</tracks>
+</lfm>"#,
+            ),
+        ] {
+            Mock::given(method("GET"))
+                .and(query_param("method", method_name))
+                .respond_with(ResponseTemplate::new(200).set_body_string(body))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        let state = test_state(&server);
+
+        let query = NewGameQuery {
+            user: Some("new".into()),
+            ..Default::default()
+        };
+        let Json(game) = newgame_album(
+            CookieJar::new(),
+            HeaderMap::new(),
+            Query(query),
+            State(state.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(game.source, Some(SeedSource::Charts));
+        assert_eq!(
+            session(&state, game.id).await.seed.name(),
+            "Blinding Lights"
+        );
+    }
+
+    #[tokio::test]
+    async fn newgame_remembers_period() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("method", "user.getTopArtists"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(TOP_ARTISTS))
+            .mount(&server)
+            .await;
+        let mut state = test_state(&server);
+        Arc::get_mut(&mut state.config).unwrap().remember_settings = true;
+        sqlx::migrate!().run(&state.pool).await.unwrap();
+
+        let signed_in =
+            sign_cookie(&state.config.jwt_key, "RJ".into(), 1, CookieJar::new()).unwrap();
+        let games = [
+            (signed_in.clone(), Some("3month")),
+            // only the signed in player can change their settings
+            (CookieJar::new(), Some("7day")),
+            (signed_in, None),
+            // and only they get them back
+            (CookieJar::new(), None),
+        ];
+        for (jar, periods) in games {
+            let query = NewGameQuery {
+                user: Some("RJ".into()),
+                periods: periods.map(ToOwned::to_owned),
+                ..Default::default()
+            };
+            let Json(_) = newgame(jar, HeaderMap::new(), Query(query), State(state.clone()))
+                .await
+                .unwrap();
+        }
+
+        let requests = server.received_requests().await.unwrap();
+        let periods: Vec<_> = requests
+            .iter()
+            .flat_map(|request| request.url.query_pairs())
+            .filter(|(key, _)| key == "period")
+            .map(|(_, period)| period.into_owned())
+            .collect();
+        assert_eq!(periods, ["3month", "7day", "3month", "overall"]);
+    }
 }
@@ -0,0 +1,240 @@
+//! Where the games in progress are kept between guesses.
+
+use std::{
+    collections::HashMap,
+    future::{self, Future},
+    sync::{Mutex, RwLock, TryLockError},
+};
+
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::{AppError, SessionState};
+
+/// Which [`SessionStore`] keeps the games in progress.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SessionBackend {
+    /// In the memory of the server. Games are lost when it restarts.
+    #[default]
+    InMemory,
+    /// In the `game_session` table, so games survive restarts.
+    Sqlite,
+}
+
+/// The games in progress, by id.
+pub trait SessionStore {
+    fn insert(
+        &self,
+        id: Uuid,
+        session: SessionState,
+    ) -> impl Future<Output = Result<(), AppError>> + Send;
+
+    /// A copy of the game `id`.
+    fn get(&self, id: Uuid) -> impl Future<Output = Result<Option<SessionState>, AppError>> + Send;
+
+    /// Change the game `id` with `update`. What `update` changed is kept even when it fails, so
+    /// that e.g. rejected guesses stay in the history.
+    ///
+    /// Fails with [`AppError::NoSession`] for unknown games, and with [`AppError::GuessInFlight`]
+    /// when the game is changed by someone else at the same time.
+    fn update<T: Send>(
+        &self,
+        id: Uuid,
+        update: impl FnOnce(&mut SessionState) -> Result<T, AppError> + Send,
+    ) -> impl Future<Output = Result<T, AppError>> + Send;
+
+    fn remove(
+        &self,
+        id: Uuid,
+    ) -> impl Future<Output = Result<Option<SessionState>, AppError>> + Send;
+}
+
+/// The configured [`SessionStore`].
+pub enum Sessions {
+    InMemory(InMemorySessions),
+    Sqlite(SqliteSessions),
+}
+
+impl Sessions {
+    pub fn new(backend: SessionBackend, pool: SqlitePool) -> Self {
+        match backend {
+            SessionBackend::InMemory => Self::InMemory(InMemorySessions::default()),
+            SessionBackend::Sqlite => Self::Sqlite(SqliteSessions(pool)),
+        }
+    }
+}
+
+impl SessionStore for Sessions {
+    async fn insert(&self, id: Uuid, session: SessionState) -> Result<(), AppError> {
+        match self {
+            Self::InMemory(sessions) => sessions.insert(id, session).await,
+            Self::Sqlite(sessions) => sessions.insert(id, session).await,
+        }
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<SessionState>, AppError> {
+        match self {
+            Self::InMemory(sessions) => sessions.get(id).await,
+            Self::Sqlite(sessions) => sessions.get(id).await,
+        }
+    }
+
+    async fn update<T: Send>(
+        &self,
+        id: Uuid,
+        update: impl FnOnce(&mut SessionState) -> Result<T, AppError> + Send,
+    ) -> Result<T, AppError> {
+        match self {
+            Self::InMemory(sessions) => sessions.update(id, update).await,
+            Self::Sqlite(sessions) => sessions.update(id, update).await,
+        }
+    }
+
+    async fn remove(&self, id: Uuid) -> Result<Option<SessionState>, AppError> {
+        match self {
+            Self::InMemory(sessions) => sessions.remove(id).await,
+            Self::Sqlite(sessions) => sessions.remove(id).await,
+        }
+    }
+}
+
+/// Games kept in memory. Guarded by a `std::sync::RwLock` (and a `Mutex` per game) that must not
+/// be held across an `.await`; every method is done before it returns its future.
+#[derive(Default)]
+pub struct InMemorySessions {
+    pub games: RwLock<HashMap<Uuid, Mutex<SessionState>>>,
+}
+
+impl SessionStore for InMemorySessions {
+    fn insert(
+        &self,
+        id: Uuid,
+        session: SessionState,
+    ) -> impl Future<Output = Result<(), AppError>> + Send {
+        self.games.write().unwrap().insert(id, Mutex::new(session));
+        future::ready(Ok(()))
+    }
+
+    fn get(&self, id: Uuid) -> impl Future<Output = Result<Option<SessionState>, AppError>> + Send {
+        let games = self.games.read().unwrap();
+        let session = games
+            .get(&id)
+            .map(|session| session.lock().unwrap().clone());
+        future::ready(Ok(session))
+    }
+
+    fn update<T: Send>(
+        &self,
+        id: Uuid,
+        update: impl FnOnce(&mut SessionState) -> Result<T, AppError> + Send,
+    ) -> impl Future<Output = Result<T, AppError>> + Send {
+        let games = self.games.read().unwrap();
+        let res = match games.get(&id).ok_or(AppError::NoSession) {
+            // updating a game twice at once would interleave the changes, so the second one is
+            // turned away instead of waiting
+            Ok(session) => match session.try_lock() {
+                Ok(mut session) => update(&mut session),
+                Err(TryLockError::WouldBlock) => Err(AppError::GuessInFlight),
+                Err(TryLockError::Poisoned(e)) => panic!("{e}"),
+            },
+            Err(e) => Err(e),
+        };
+        future::ready(res)
+    }
+
+    fn remove(
+        &self,
+        id: Uuid,
+    ) -> impl Future<Output = Result<Option<SessionState>, AppError>> + Send {
+        let session = self.games.write().unwrap().remove(&id);
+        future::ready(Ok(session.map(|session| session.into_inner().unwrap())))
+    }
+}
+
+/// Games kept in the `game_session` table as JSON.
+pub struct SqliteSessions(SqlitePool);
+
+fn decode(state: &str) -> Result<SessionState, AppError> {
+    serde_json::from_str(state).map_err(AppError::internal)
+}
+
+fn encode(session: &SessionState) -> Result<String, AppError> {
+    serde_json::to_string(session).map_err(AppError::internal)
+}
+
+impl SessionStore for SqliteSessions {
+    async fn insert(&self, id: Uuid, session: SessionState) -> Result<(), AppError> {
+        let id = id.to_string();
+        let state = encode(&session)?;
+        sqlx::query!(
+            "INSERT INTO game_session (id, state) VALUES (?, ?)",
+            id,
+            state
+        )
+        .execute(&self.0)
+        .await
+        .map_err(AppError::internal)?;
+        Ok(())
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<SessionState>, AppError> {
+        let id = id.to_string();
+        let state = sqlx::query_scalar!("SELECT state FROM game_session WHERE id = ?", id)
+            .fetch_optional(&self.0)
+            .await
+            .map_err(AppError::internal)?;
+        state.as_deref().map(decode).transpose()
+    }
+
+    async fn update<T: Send>(
+        &self,
+        id: Uuid,
+        update: impl FnOnce(&mut SessionState) -> Result<T, AppError> + Send,
+    ) -> Result<T, AppError> {
+        let id = id.to_string();
+        let row = sqlx::query!("SELECT state, revision FROM game_session WHERE id = ?", id)
+            .fetch_optional(&self.0)
+            .await
+            .map_err(AppError::internal)?
+            .ok_or(AppError::NoSession)?;
+
+        let mut session = decode(&row.state)?;
+        let res = update(&mut session);
+        let state = encode(&session)?;
+        // only written if nobody else changed the game since it was read
+        let written = sqlx::query!(
+            "UPDATE game_session SET state = ?, revision = revision + 1
+            WHERE id = ? AND revision = ?",
+            state,
+            id,
+            row.revision
+        )
+        .execute(&self.0)
+        .await
+        .map_err(AppError::internal)?;
+        if written.rows_affected() == 0 {
+            // finished and removed by someone else, or changed and still there
+            let exists = sqlx::query_scalar!("SELECT id FROM game_session WHERE id = ?", id)
+                .fetch_optional(&self.0)
+                .await
+                .map_err(AppError::internal)?;
+            return Err(match exists {
+                Some(_) => AppError::GuessInFlight,
+                None => AppError::NoSession,
+            });
+        }
+        res
+    }
+
+    async fn remove(&self, id: Uuid) -> Result<Option<SessionState>, AppError> {
+        let id = id.to_string();
+        let state =
+            sqlx::query_scalar!("DELETE FROM game_session WHERE id = ? RETURNING state", id)
+                .fetch_optional(&self.0)
+                .await
+                .map_err(AppError::internal)?;
+        state.as_deref().map(decode).transpose()
+    }
+}